@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// The kind of an activity in a source.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ActivityKind {
+    /// A commit, like the one at the tip of a local branch.
+    Commit,
+    /// A push to a branch or tag.
+    Push,
+    /// A merge, like the one of a pull request.
+    Merge,
+    /// The creation of a branch, tag or repository.
+    Create,
+    /// The deletion of a branch or tag.
+    Delete,
+    /// A note added to a commit.
+    Note,
+    /// Changes in the working tree that aren't committed yet.
+    Snack,
+    /// Anything else, by the name the hoster uses for it.
+    Other(String),
+}
+
+/// A single activity observed in a source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Activity {
+    /// When the activity happened.
+    pub timestamp: DateTime<Utc>,
+    /// What happened.
+    pub kind: ActivityKind,
+    /// Who did it.
+    pub actor: Option<String>,
+    /// The email address of who did it, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// The branch or tag it happened on.
+    pub git_ref: Option<String>,
+    /// The commit message or title, if any.
+    pub message: Option<String>,
+}
+
+impl Activity {
+    /// The name of a ref as git shortens it, like `main` for `refs/heads/main`, `origin/main`
+    /// for `refs/remotes/origin/main` or `notes/commits` for `refs/notes/commits`.
+    pub fn short_ref(git_ref: &str) -> &str {
+        ["refs/heads/", "refs/remotes/", "refs/tags/", "refs/"]
+            .into_iter()
+            .find_map(|prefix| git_ref.strip_prefix(prefix))
+            .unwrap_or(git_ref)
+    }
+
+    /// The time of the newest of the given activities.
+    pub fn latest(activities: &[Activity]) -> Option<DateTime<Utc>> {
+        activities.iter().map(|activity| activity.timestamp).max()
+    }
+}
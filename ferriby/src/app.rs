@@ -1,22 +1,39 @@
-use std::fmt::Display;
+use std::{collections::HashMap, fmt::Display};
 
 use crate::{
-    event::{AppEvent, Event, EventHandler, IntervalSecs},
+    activity::{Activity, ActivityKind},
+    art::ArtPack,
+    error::ActivityError,
+    event::{AppEvent, Event, EventHandler},
+    filter::{AuthorFilter, IgnoreRules, RefFilter},
     forgejo::ForgejoSource,
     git::GitSource,
+    githoster::{ConnectionSettings, GitHosterClient, HttpSettings, RateLimit},
     github::GitHubSource,
     gitlab::GitLabSource,
+    history::{History, HistoryFile},
+    keys::{Action, KeyBindings},
+    rhythm::rhythm_start,
+    state::{StateFile, StoredState},
+    theme::Theme,
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeDelta, Utc};
+use color_eyre::eyre::WrapErr;
 use crossterm::event::KeyEventKind;
 use ratatui::{
     DefaultTerminal,
-    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind},
+    layout::{Position, Rect},
 };
-use tokio::task::JoinError;
 
 pub trait ActivitySource {
-    fn get_last_activity(self) -> impl Future<Output = Option<DateTime<Utc>>>;
+    /// The current activities of the source, like the tips of its branches, and the ones since
+    /// `since` that make up its rhythm. Older activities may be left out.
+    fn get_activities(
+        self,
+        client: GitHosterClient,
+        since: DateTime<Utc>,
+    ) -> impl Future<Output = Result<Vec<Activity>, ActivityError>>;
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -27,6 +44,107 @@ pub enum Source {
     Forgejo(ForgejoSource),
 }
 
+impl Source {
+    /// The settings shared by all kinds of sources.
+    pub fn settings(&self) -> &SourceSettings {
+        match self {
+            Source::Git(source) => &source.settings,
+            Source::GitHub(source) => &source.settings,
+            Source::GitLab(source) => &source.settings,
+            Source::Forgejo(source) => &source.settings,
+        }
+    }
+
+    /// The settings shared by all kinds of sources.
+    pub fn settings_mut(&mut self) -> &mut SourceSettings {
+        match self {
+            Source::Git(source) => &mut source.settings,
+            Source::GitHub(source) => &mut source.settings,
+            Source::GitLab(source) => &mut source.settings,
+            Source::Forgejo(source) => &mut source.settings,
+        }
+    }
+
+    /// The host the API of a hosted source lives at.
+    pub fn api_host(&self) -> Option<String> {
+        match self {
+            Source::Git(_) => None,
+            Source::GitHub(_) => Some("api.github.com".into()),
+            Source::GitLab(source) => Some(source.hostname.clone()),
+            Source::Forgejo(source) => source.base_url.host_str().map(String::from),
+        }
+    }
+
+    /// The interval to check the source in.
+    ///
+    /// Without a configured interval, local repos are checked every 3 seconds and hosted ones
+    /// every 5 seconds with a PAT or every 60 seconds without one.
+    pub fn interval_secs(&self) -> f32 {
+        let default_secs = match self {
+            Source::Git(_) => 3.0,
+            Source::GitHub(GitHubSource { pat, .. })
+            | Source::GitLab(GitLabSource { pat, .. })
+            | Source::Forgejo(ForgejoSource { pat, .. }) => {
+                if pat.is_some() {
+                    5.0
+                } else {
+                    60.0
+                }
+            }
+        };
+        self.settings().interval_secs.unwrap_or(default_secs)
+    }
+}
+
+/// Settings that can be given for every kind of source.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceSettings {
+    /// When the ferris of the source is buzzing or okayish.
+    pub thresholds: Thresholds,
+    /// The interval to check the source in, if not the default one.
+    pub interval_secs: Option<f32>,
+    /// How to connect to the git hoster of the source.
+    pub connection: ConnectionSettings,
+    /// Which branches and other refs count.
+    pub branches: RefFilter,
+    /// Whose activities count.
+    pub authors: AuthorFilter,
+    /// Which activities don't count, like the ones of bots.
+    pub ignore: IgnoreRules,
+}
+
+impl SourceSettings {
+    /// Does the activity count for the source?
+    pub fn allows(&self, activity: &Activity) -> bool {
+        self.branches.allows(activity)
+            && self.authors.allows(activity)
+            && self.ignore.allows(activity)
+    }
+
+    /// The given activities without the ones that don't count for the source.
+    pub fn counting(&self, mut activities: Vec<Activity>) -> Vec<Activity> {
+        activities.retain(|activity| self.allows(activity));
+        activities
+    }
+}
+
+impl ActivitySource for Source {
+    async fn get_activities(
+        self,
+        client: GitHosterClient,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Activity>, ActivityError> {
+        let settings = self.settings().clone();
+        let activities = match self {
+            Source::Git(source) => source.get_activities(client, since).await,
+            Source::GitHub(source) => source.get_activities(client, since).await,
+            Source::GitLab(source) => source.get_activities(client, since).await,
+            Source::Forgejo(source) => source.get_activities(client, since).await,
+        }?;
+        Ok(settings.counting(activities))
+    }
+}
+
 impl Display for Source {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -51,29 +169,77 @@ impl Display for Source {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// How happy a ferris is, from the least to the most.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Happiness {
+    #[default]
     Undecided,
     Sad,
     Okayish,
     Buzzing,
 }
 
+/// The maximal ages of the last activity for a buzzing or okayish ferris.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    /// Activity younger than this makes the ferris buzzing.
+    buzzing: TimeDelta,
+    /// Activity younger than this makes the ferris okayish.
+    okayish: TimeDelta,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            buzzing: TimeDelta::hours(24),
+            okayish: TimeDelta::hours(24 * 7),
+        }
+    }
+}
+
+impl Thresholds {
+    /// Constructs new [`Thresholds`], making sure they are positive and increasing.
+    pub fn new(buzzing: TimeDelta, okayish: TimeDelta) -> Result<Self, String> {
+        if buzzing <= TimeDelta::zero() {
+            Err("the buzzing threshold must be positive".into())
+        } else if okayish <= buzzing {
+            Err("the okayish threshold must be greater than the buzzing threshold".into())
+        } else {
+            Ok(Self { buzzing, okayish })
+        }
+    }
+
+    pub fn buzzing(&self) -> TimeDelta {
+        self.buzzing
+    }
+
+    pub fn okayish(&self) -> TimeDelta {
+        self.okayish
+    }
+}
+
+/// How far an activity may lie in the future before we blame the clocks.
+const CLOCK_SKEW_TOLERANCE: TimeDelta = TimeDelta::minutes(5);
+
 impl Happiness {
-    fn from_last_activity(last_activity: Option<DateTime<Utc>>) -> Self {
+    fn from_last_activity(
+        last_activity: Option<DateTime<Utc>>,
+        thresholds: &Thresholds,
+    ) -> Result<Self, ActivityError> {
         if let Some(last_activity) = last_activity {
             let now = chrono::Utc::now();
-            if now < last_activity {
-                panic!("commits from the future");
+            if last_activity - now > CLOCK_SKEW_TOLERANCE {
+                return Err(ActivityError::ClockSkew);
             }
             let diff = now - last_activity;
-            match diff {
-                _ if diff < chrono::TimeDelta::hours(24) => Happiness::Buzzing,
-                _ if diff < chrono::TimeDelta::hours(24 * 7) => Happiness::Okayish,
+            let happiness = match diff {
+                _ if diff < thresholds.buzzing => Happiness::Buzzing,
+                _ if diff < thresholds.okayish => Happiness::Okayish,
                 _ => Happiness::Sad,
-            }
+            };
+            Ok(happiness)
         } else {
-            Happiness::Undecided
+            Ok(Happiness::Undecided)
         }
     }
 }
@@ -89,193 +255,482 @@ impl From<Happiness> for String {
     }
 }
 
+/// Settings of the whole application.
+#[derive(Debug, Clone, Default)]
+pub struct AppSettings {
+    /// How to talk to the git hosters.
+    pub http: HttpSettings,
+    /// Where to remember the states of the sources across runs, if at all.
+    pub state_file: Option<StateFile>,
+    /// Where to log the activities of the sources, if at all.
+    pub history_file: Option<HistoryFile>,
+    /// How to cycle through the sources on their own, if at all.
+    pub kiosk: Option<KioskSettings>,
+    /// The theme to start with.
+    pub theme: Theme,
+    /// The ASCII art of the ferrises.
+    pub art: ArtPack,
+    /// Which keys trigger which actions.
+    pub keys: KeyBindings,
+}
+
+/// How to cycle through the sources on a wall display nobody interacts with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KioskSettings {
+    /// How long to show each source.
+    pub interval_secs: f32,
+    /// Skip the sources whose ferris is buzzing anyway?
+    pub skip_buzzing: bool,
+}
+
+/// The cached state of a single source.
+#[derive(Debug, Clone, Default)]
+pub struct SourceState {
+    /// The last activity seen in the source.
+    pub last_activity: Option<DateTime<Utc>>,
+    /// When the source was checked successfully the last time.
+    pub last_checked: Option<DateTime<Utc>>,
+    /// How happy the ferris of the source is.
+    pub happiness: Happiness,
+    /// Is a check of the source currently running?
+    pub in_flight: bool,
+    /// The error of the last check, if it failed.
+    pub error: Option<ActivityError>,
+    /// All activities ever observed in the source.
+    pub history: History,
+    /// When the uncommitted changes in the working tree were edited the last time, if there
+    /// are any.
+    pub snacking: Option<DateTime<Utc>>,
+}
+
+/// How many check intervals may pass without a successful check before a state is stale.
+const STALE_AFTER_INTERVALS: f32 = 3.0;
+
+/// The shortest time without a successful check before a state is stale.
+const MIN_STALE_AFTER: TimeDelta = TimeDelta::minutes(1);
+
+impl SourceState {
+    /// Restores the state remembered from an earlier run.
+    fn from_stored(stored: StoredState, thresholds: &Thresholds) -> Self {
+        let (happiness, error) =
+            match Happiness::from_last_activity(stored.last_activity, thresholds) {
+                Ok(happiness) => (happiness, stored.error),
+                Err(e) => (Happiness::Undecided, Some(e)),
+            };
+        Self {
+            last_activity: stored.last_activity,
+            last_checked: stored.last_checked,
+            happiness,
+            error,
+            ..Self::default()
+        }
+    }
+
+    /// The part of the state worth remembering across runs.
+    fn to_stored(&self) -> StoredState {
+        StoredState {
+            last_activity: self.last_activity,
+            last_checked: self.last_checked,
+            error: self.error.clone(),
+        }
+    }
+
+    /// Since when to look for activities in the next check: the whole rhythm until it is in
+    /// the history, only what's new since the newest activity in the history after that.
+    fn check_since(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let since = rhythm_start(now);
+        self.history
+            .activities()
+            .last()
+            .map_or(since, |newest| newest.timestamp.max(since))
+    }
+
+    /// Is the state outdated, as the source wasn't checked successfully for a while?
+    pub fn is_stale(&self, interval_secs: f32, now: DateTime<Utc>) -> bool {
+        let Some(last_checked) = self.last_checked else {
+            return false;
+        };
+        let stale_after =
+            TimeDelta::milliseconds((interval_secs * STALE_AFTER_INTERVALS * 1000.0) as i64)
+                .max(MIN_STALE_AFTER);
+        now - last_checked > stale_after
+    }
+}
+
+/// For how many animation ticks a ferris reacts to being clicked.
+const REACTION_TICKS: usize = 4;
+
+/// How often the states are written to the state file at most while running.
+const STATE_SAVE_INTERVAL: TimeDelta = TimeDelta::seconds(30);
+
 /// Application.
 #[derive(Debug)]
 pub struct App {
     /// Is the application running?
     pub running: bool,
-    /// How happy we are.
-    pub happiness: Happiness,
     /// Event handler.
     pub events: EventHandler,
     /// Repos to monitor.
     pub sources: Vec<Source>,
+    /// The cached state of each repo, indexed like `sources`.
+    pub states: Vec<SourceState>,
+    /// The client for the git hosters, shared by all checks.
+    pub hoster_client: GitHosterClient,
     /// The currently selected repo.
     pub selected: usize,
     /// Which animation to show.
     pub animation: usize,
+    /// Are all sources shown at once in a grid?
+    pub grid: bool,
+    /// How to cycle through the sources on their own, if at all.
+    pub kiosk: Option<KioskSettings>,
+    /// The themes to cycle through.
+    pub themes: Vec<Theme>,
+    /// The currently used theme.
+    pub theme: usize,
+    /// The ASCII art of the ferrises.
+    pub art: ArtPack,
+    /// Which keys trigger which actions.
+    pub keys: KeyBindings,
+    /// Is the help shown?
+    pub help: bool,
+    /// For how many more animation ticks the selected ferris reacts to being clicked.
+    pub reaction: usize,
+    /// The area the app was rendered to the last time, to find what was clicked.
+    pub area: Rect,
+    /// Where to remember the states of the sources across runs, if at all.
+    pub state_file: Option<StateFile>,
+    /// When the states were written to the state file the last time.
+    pub state_saved: Option<DateTime<Utc>>,
+    /// Where to log the activities of the sources, if at all.
+    pub history_file: Option<HistoryFile>,
 }
 
 impl Default for App {
     fn default() -> Self {
         Self {
             running: true,
-            events: EventHandler::new(IntervalSecs::default()),
-            happiness: Happiness::Undecided,
+            events: EventHandler::new(vec![], None, ArtPack::default().frame_secs),
             sources: vec![],
+            states: vec![],
+            hoster_client: GitHosterClient::default(),
             selected: 0,
             animation: 0,
+            grid: false,
+            kiosk: None,
+            themes: Theme::builtins(),
+            theme: 0,
+            art: ArtPack::default(),
+            keys: KeyBindings::default(),
+            help: false,
+            reaction: 0,
+            area: Rect::default(),
+            state_file: None,
+            state_saved: None,
+            history_file: None,
         }
     }
 }
 
 impl App {
     /// Constructs a new instance of [`App`].
-    pub fn new(sources: Vec<Source>) -> Self {
-        let git_interval_secs = sources
-            .iter()
-            .find(|source| matches!(source, Source::Git(_)))
-            .map(|_| 3.0);
-
-        let gh_interval_secs = {
-            let source = sources.iter().find_map(|source| match source {
-                Source::GitHub(x) => Some(x),
-                _ => None,
-            });
-            match source {
-                Some(source) if source.pat.is_some() => Some(5.0),
-                Some(_) => Some(60.0),
-                _ => None,
+    pub fn new(sources: Vec<Source>, settings: AppSettings) -> color_eyre::Result<Self> {
+        let source_intervals = sources.iter().map(Source::interval_secs).collect();
+        let mut themes = Theme::builtins();
+        let theme = match themes.iter().position(|theme| *theme == settings.theme) {
+            Some(index) => index,
+            None => {
+                themes.push(settings.theme);
+                themes.len() - 1
             }
         };
+        let mut stored = settings
+            .state_file
+            .as_ref()
+            .map(StateFile::load)
+            .unwrap_or_default();
+        let source_settings: HashMap<String, &SourceSettings> = sources
+            .iter()
+            .map(|source| (source.to_string(), source.settings()))
+            .collect();
+        let mut histories = settings
+            .history_file
+            .as_ref()
+            // older activities don't show in the rhythm anymore, and ones logged before the
+            // filters of a source changed may not count anymore
+            .map(|history_file| {
+                history_file.load(rhythm_start(Utc::now()), |name, activity| {
+                    source_settings
+                        .get(name)
+                        .is_some_and(|settings| settings.allows(activity))
+                })
+            })
+            .unwrap_or_default();
+        let states = sources
+            .iter()
+            .map(|source| {
+                let name = source.to_string();
+                let mut state = match stored.remove(&name) {
+                    Some(stored) => SourceState::from_stored(stored, &source.settings().thresholds),
+                    None => SourceState::default(),
+                };
+                state.history = histories.remove(&name).unwrap_or_default();
+                state
+            })
+            .collect();
+        let hoster_client = GitHosterClient::new(settings.http);
+        for source in sources.iter().filter(|source| source.api_host().is_some()) {
+            hoster_client
+                .add_connection(&source.settings().connection)
+                .wrap_err_with(|| format!("failed to set up the connection for {source}"))?;
+        }
 
-        let gl_interval_secs = {
-            let source = sources.iter().find_map(|source| match source {
-                Source::GitLab(x) => Some(x),
-                _ => None,
-            });
-            match source {
-                Some(source) if source.pat.is_some() => Some(5.0),
-                Some(_) => Some(60.0),
-                _ => None,
-            }
-        };
-
-        let fj_interval_secs = {
-            let source = sources.iter().find_map(|source| match source {
-                Source::Forgejo(x) => Some(x),
-                _ => None,
-            });
-            match source {
-                Some(source) if source.pat.is_some() => Some(5.0),
-                Some(_) => Some(60.0),
-                _ => None,
-            }
-        };
-
-        let intervals = IntervalSecs {
-            git: git_interval_secs,
-            github: gh_interval_secs,
-            gitlab: gl_interval_secs,
-            forgejo: fj_interval_secs,
-        };
-
-        Self {
+        Ok(Self {
             running: true,
-            events: EventHandler::new(intervals),
-            happiness: Happiness::Undecided,
+            events: EventHandler::new(
+                source_intervals,
+                settings.kiosk.as_ref().map(|kiosk| kiosk.interval_secs),
+                settings.art.frame_secs,
+            ),
             sources,
+            states,
+            hoster_client,
             selected: 0,
             animation: 0,
-        }
+            grid: false,
+            kiosk: settings.kiosk,
+            themes,
+            theme,
+            art: settings.art,
+            keys: settings.keys,
+            help: false,
+            reaction: 0,
+            area: Rect::default(),
+            state_file: settings.state_file,
+            state_saved: None,
+            history_file: settings.history_file,
+        })
     }
 
     /// Run the application's main loop.
     pub async fn run(mut self, mut terminal: DefaultTerminal) -> color_eyre::Result<()> {
         while self.running {
-            terminal.draw(|frame| frame.render_widget(&self, frame.area()))?;
+            self.area = terminal
+                .draw(|frame| frame.render_widget(&self, frame.area()))?
+                .area;
             match self.events.next().await? {
-                Event::GitTick => self.git_tick().await,
-                Event::GitHubTick => self.github_tick().await,
-                Event::GitLabTick => self.gitlab_tick().await,
-                Event::ForgejoTick => self.forgejo_tick().await,
+                Event::SourceTick(index) => self.source_tick(index),
                 Event::AnimationTick => self.animation_tick(),
-                Event::Crossterm(event) => {
-                    if let crossterm::event::Event::Key(key_event) = event {
-                        self.handle_key_events(key_event)?
+                Event::KioskTick => self.kiosk_tick(),
+                Event::Crossterm(event) => match event {
+                    crossterm::event::Event::Key(key_event) => self.handle_key_events(key_event)?,
+                    crossterm::event::Event::Mouse(mouse_event) => {
+                        self.handle_mouse_events(mouse_event)
                     }
-                }
+                    _ => {}
+                },
                 Event::App(app_event) => match app_event {
                     AppEvent::Quit => self.quit(),
+                    AppEvent::Activities(index, result) => {
+                        self.handle_activities(index, result);
+                        if self
+                            .state_saved
+                            .is_none_or(|saved| Utc::now() - saved > STATE_SAVE_INTERVAL)
+                        {
+                            // a failing save is retried later and reported on quit
+                            let _ = self.save_states();
+                        }
+                    }
                 },
             }
         }
-        Ok(())
+        self.save_states()
+    }
+
+    /// Writes the states of the sources to the state file, if there is one.
+    fn save_states(&mut self) -> color_eyre::Result<()> {
+        let Some(state_file) = &self.state_file else {
+            return Ok(());
+        };
+        self.state_saved = Some(Utc::now());
+        let states: HashMap<String, StoredState> = self
+            .sources
+            .iter()
+            .zip(&self.states)
+            .map(|(source, state)| (source.to_string(), state.to_stored()))
+            .collect();
+        state_file.save(states).wrap_err_with(|| {
+            format!(
+                "failed to save the state to {}",
+                state_file.path().display()
+            )
+        })
     }
 
     /// Handles the key events and updates the state of [`App`].
     pub fn handle_key_events(&mut self, key_event: KeyEvent) -> color_eyre::Result<()> {
-        match key_event.code {
-            KeyCode::Esc | KeyCode::Char('q') => self.events.send(AppEvent::Quit),
-            KeyCode::Char('c' | 'C') if key_event.modifiers == KeyModifiers::CONTROL => {
-                self.events.send(AppEvent::Quit)
+        if key_event.kind != KeyEventKind::Press {
+            return Ok(());
+        }
+        // Esc closes the help before it quits
+        if self.help && key_event.code == KeyCode::Esc {
+            self.help = false;
+            return Ok(());
+        }
+        let Some(action) = self.keys.action(&key_event) else {
+            return Ok(());
+        };
+        let count = self.sources.len().max(1);
+        match action {
+            Action::Quit => self.events.send(AppEvent::Quit),
+            Action::Next => self.selected = (self.selected + 1) % count,
+            Action::Previous => self.selected = (self.selected + count - 1) % count,
+            Action::First => self.selected = 0,
+            Action::Last => self.selected = count - 1,
+            Action::Refresh => self.source_tick(self.selected),
+            Action::Grid => self.grid = !self.grid,
+            Action::Theme => self.theme = (self.theme + 1) % self.themes.len(),
+            Action::Help => self.help = !self.help,
+        }
+        Ok(())
+    }
+
+    /// Handles the mouse events: a click selects a source or makes its ferris react and the
+    /// wheel moves the selection through the list.
+    pub fn handle_mouse_events(&mut self, mouse_event: MouseEvent) {
+        let position = Position::new(mouse_event.column, mouse_event.row);
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if self.help {
+                    self.help = false;
+                } else if self.is_ferris_at(self.area, position) {
+                    self.reaction = REACTION_TICKS;
+                } else if let Some(index) = self.source_at(self.area, position) {
+                    self.selected = index;
+                }
             }
-            KeyCode::Down if key_event.kind == KeyEventKind::Press => {
-                self.happiness = Happiness::Undecided;
-                self.selected = (self.selected + 1) % self.sources.len();
-                self.events.restart();
+            MouseEventKind::ScrollDown if self.is_list_at(self.area, position) => {
+                self.selected = (self.selected + 1).min(self.sources.len().saturating_sub(1));
             }
-            KeyCode::Up if key_event.kind == KeyEventKind::Press => {
-                self.happiness = Happiness::Undecided;
-                self.selected = {
-                    if self.selected == 0 {
-                        self.sources.len() - 1
-                    } else {
-                        self.selected.saturating_sub(1)
-                    }
-                };
-                self.events.restart();
+            MouseEventKind::ScrollUp if self.is_list_at(self.area, position) => {
+                self.selected = self.selected.saturating_sub(1);
             }
             _ => {}
         }
-        Ok(())
     }
 
-    /// Handle the last_activity
-    fn handle_last_activity(&mut self, last_activity: Result<Option<DateTime<Utc>>, JoinError>) {
-        match last_activity {
-            Ok(last_event) => {
-                self.happiness = Happiness::from_last_activity(last_event);
-            }
-            Err(_) => self.running = false,
-        }
+    /// The currently used theme.
+    pub fn theme(&self) -> &Theme {
+        &self.themes[self.theme]
     }
 
-    /// Handles the git_tick event.
-    async fn git_tick(&mut self) {
-        if let Source::Git(source) = &self.sources[self.selected] {
-            let last_activity = tokio::spawn(source.clone().get_last_activity()).await;
-            self.handle_last_activity(last_activity);
-        };
+    /// The happiness of the currently selected source.
+    pub fn happiness(&self) -> Happiness {
+        self.states
+            .get(self.selected)
+            .map(|state| state.happiness)
+            .unwrap_or_default()
     }
 
-    /// Handles the github_tick event.
-    async fn github_tick(&mut self) {
-        if let Source::GitHub(source) = &self.sources[self.selected] {
-            let last_activity = tokio::spawn(source.clone().get_last_activity()).await;
-            self.handle_last_activity(last_activity);
-        };
+    /// The last known rate limit of the host of the currently selected source.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        let host = self.sources.get(self.selected)?.api_host()?;
+        self.hoster_client.rate_limit(&host)
     }
 
-    /// Handles the gitlab_tick event.
-    async fn gitlab_tick(&mut self) {
-        if let Source::GitLab(source) = &self.sources[self.selected] {
-            let last_activity = tokio::spawn(source.clone().get_last_activity()).await;
-            self.handle_last_activity(last_activity);
-        };
+    /// Handle the result of checking the activities of the source with the given index.
+    fn handle_activities(&mut self, index: usize, result: Result<Vec<Activity>, ActivityError>) {
+        if let Some(state) = self.states.get_mut(index) {
+            let thresholds = &self.sources[index].settings().thresholds;
+            state.in_flight = false;
+            let happiness = result.and_then(|activities| {
+                // snacks come and go with the working tree, so they aren't part of the history
+                let (snacks, activities): (Vec<_>, Vec<_>) = activities
+                    .into_iter()
+                    .partition(|activity| activity.kind == ActivityKind::Snack);
+                let new = state.history.record(&activities);
+                if let Some(history_file) = &self.history_file {
+                    // the history is a nice to have, so failing to log it doesn't fail the check
+                    let _ = history_file.append(&self.sources[index].to_string(), &new);
+                }
+                // when nothing that counts turned up, the ferris was last fed by what's logged
+                state.last_activity = Activity::latest(&activities).or_else(|| {
+                    state
+                        .history
+                        .activities()
+                        .last()
+                        .map(|newest| newest.timestamp)
+                });
+                // files are often modified in the future after checkouts or on network drives,
+                // which is no reason to fail the check
+                let now = Utc::now();
+                state.snacking = Activity::latest(&snacks).map(|snacking| snacking.min(now));
+                let fed = Happiness::from_last_activity(state.last_activity, thresholds)?;
+                // snacking keeps a ferris from starving, but only proper meals make it buzz
+                let snacked = Happiness::from_last_activity(state.snacking, thresholds)
+                    .unwrap_or_default()
+                    .min(Happiness::Okayish);
+                Ok(fed.max(snacked))
+            });
+            match happiness {
+                Ok(happiness) => {
+                    state.happiness = happiness;
+                    state.last_checked = Some(Utc::now());
+                    state.error = None;
+                }
+                Err(e) => state.error = Some(e),
+            }
+        }
     }
 
-    /// Handles the forgejo_tick event.
-    async fn forgejo_tick(&mut self) {
-        if let Source::Forgejo(source) = &self.sources[self.selected] {
-            let last_activity = tokio::spawn(source.clone().get_last_activity()).await;
-            self.handle_last_activity(last_activity);
+    /// Handles the source_tick event by checking the source in the background.
+    fn source_tick(&mut self, index: usize) {
+        let Some(state) = self.states.get_mut(index) else {
+            return;
         };
+        if state.in_flight {
+            return;
+        }
+        state.in_flight = true;
+        let since = state.check_since(Utc::now());
+
+        let source = self.sources[index].clone();
+        let client = self.hoster_client.clone();
+        let sender = self.events.sender();
+        tokio::spawn(async move {
+            let result = match source {
+                // git2 only blocks, so local repos are checked off the runtime workers
+                Source::Git(source) => {
+                    tokio::task::spawn_blocking(move || {
+                        source
+                            .get_activities_blocking(since)
+                            .map(|activities| source.settings.counting(activities))
+                    })
+                    .await
+                }
+                source => tokio::spawn(source.get_activities(client, since)).await,
+            }
+            .unwrap_or_else(|e| Err(ActivityError::Internal(e.to_string())));
+            let _ = sender.send(Event::App(AppEvent::Activities(index, result)));
+        });
     }
 
     /// Handles the animation_tick event of the terminal.
     fn animation_tick(&mut self) {
         self.animation = self.animation.wrapping_add(1);
+        self.reaction = self.reaction.saturating_sub(1);
+    }
+
+    /// Handles the kiosk_tick event by selecting the next source worth showing.
+    fn kiosk_tick(&mut self) {
+        let skip_buzzing = self.kiosk.as_ref().is_some_and(|kiosk| kiosk.skip_buzzing);
+        let count = self.states.len();
+        if let Some(next) = (1..=count)
+            .map(|offset| (self.selected + offset) % count)
+            .find(|index| !skip_buzzing || self.states[*index].happiness != Happiness::Buzzing)
+        {
+            self.selected = next;
+        }
     }
 
     /// Set running to false to quit the application.
@@ -287,15 +742,27 @@ impl App {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::github::GitHubSource;
+    use crate::{activity::ActivityKind, git::GitRefs, github::GitHubSource};
     use reqwest::Url;
 
+    fn commit_at(timestamp: DateTime<Utc>) -> Activity {
+        Activity {
+            timestamp,
+            kind: ActivityKind::Commit,
+            actor: None,
+            email: None,
+            git_ref: None,
+            message: None,
+        }
+    }
+
     #[test]
     fn github_display() {
         let source = Source::GitHub(GitHubSource {
             owner: "owner_name".into(),
             repo: "repo_name".into(),
             pat: None,
+            settings: SourceSettings::default(),
         });
         let s = format!("{source}");
         assert_eq!("github: owner_name/repo_name", s);
@@ -305,6 +772,9 @@ mod tests {
     fn git_display() {
         let source = Source::Git(GitSource {
             path: "abc/cde/fgh".into(),
+            refs: GitRefs::default(),
+            snacking: false,
+            settings: SourceSettings::default(),
         });
         let s = format!("{source}");
         assert_eq!("git: abc/cde/fgh", s);
@@ -317,8 +787,461 @@ mod tests {
             owner: "owner_name".into(),
             repo: "repo_name".into(),
             pat: None,
+            settings: SourceSettings::default(),
         });
         let s = format!("{source}");
         assert_eq!("localhost: owner_name/repo_name", s);
     }
+
+    #[tokio::test]
+    async fn last_activity_is_cached_per_source() {
+        let sources = vec![
+            Source::Git(GitSource {
+                path: "abc/cde/fgh".into(),
+                refs: GitRefs::default(),
+                snacking: false,
+                settings: SourceSettings::default(),
+            }),
+            Source::Git(GitSource {
+                path: "ijk/lmn/opq".into(),
+                refs: GitRefs::default(),
+                snacking: false,
+                settings: SourceSettings::default(),
+            }),
+        ];
+        let mut app = App::new(sources, AppSettings::default()).unwrap();
+        app.handle_activities(1, Ok(vec![commit_at(Utc::now())]));
+
+        assert_eq!(app.states[0].happiness, Happiness::Undecided);
+        assert!(app.states[0].last_checked.is_none());
+        assert_eq!(app.states[1].happiness, Happiness::Buzzing);
+        assert!(!app.states[1].in_flight);
+
+        app.selected = 1;
+        assert_eq!(app.happiness(), Happiness::Buzzing);
+    }
+
+    #[test]
+    fn rhythm_is_only_checked_until_it_is_in_the_history() {
+        let now = Utc::now();
+        let mut state = SourceState::default();
+        assert_eq!(state.check_since(now), rhythm_start(now));
+
+        state
+            .history
+            .record(&[commit_at(now - TimeDelta::weeks(30))]);
+        assert_eq!(state.check_since(now), rhythm_start(now));
+
+        let newest = now - TimeDelta::hours(2);
+        state
+            .history
+            .record(&[commit_at(now - TimeDelta::days(3)), commit_at(newest)]);
+        assert_eq!(state.check_since(now), newest);
+    }
+
+    #[tokio::test]
+    async fn last_activity_is_the_newest() {
+        let sources = vec![Source::Git(GitSource {
+            path: "abc/cde/fgh".into(),
+            refs: GitRefs::default(),
+            snacking: false,
+            settings: SourceSettings::default(),
+        })];
+        let mut app = App::new(sources, AppSettings::default()).unwrap();
+        let newest = Utc::now() - TimeDelta::hours(1);
+        let oldest = Utc::now() - TimeDelta::days(10);
+        app.handle_activities(0, Ok(vec![commit_at(oldest), commit_at(newest)]));
+
+        assert_eq!(app.states[0].last_activity, Some(newest));
+        assert_eq!(
+            app.states[0].history.activities(),
+            &[commit_at(oldest), commit_at(newest)]
+        );
+    }
+
+    #[tokio::test]
+    async fn last_activity_falls_back_to_the_history() {
+        let sources = vec![Source::Git(GitSource {
+            path: "abc/cde/fgh".into(),
+            refs: GitRefs::default(),
+            snacking: false,
+            settings: SourceSettings::default(),
+        })];
+        let mut app = App::new(sources, AppSettings::default()).unwrap();
+        let logged = Utc::now() - TimeDelta::weeks(30);
+        app.states[0].history.record(&[commit_at(logged)]);
+        app.handle_activities(0, Ok(vec![]));
+
+        assert_eq!(app.states[0].last_activity, Some(logged));
+        assert_eq!(app.states[0].happiness, Happiness::Sad);
+    }
+
+    #[tokio::test]
+    async fn git_sources_are_checked_in_the_background() {
+        let sources = vec![Source::Git(GitSource {
+            path: "/this/path/does/not/exist".into(),
+            refs: GitRefs::default(),
+            snacking: false,
+            settings: SourceSettings::default(),
+        })];
+        let mut app = App::new(sources, AppSettings::default()).unwrap();
+        app.source_tick(0);
+        assert!(app.states[0].in_flight);
+
+        loop {
+            if let Event::App(AppEvent::Activities(index, result)) =
+                app.events.next().await.unwrap()
+            {
+                assert_eq!(index, 0);
+                assert!(matches!(result, Err(ActivityError::NotFound(_))));
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_check_keeps_app_running() {
+        let sources = vec![Source::Git(GitSource {
+            path: "abc/cde/fgh".into(),
+            refs: GitRefs::default(),
+            snacking: false,
+            settings: SourceSettings::default(),
+        })];
+        let mut app = App::new(sources, AppSettings::default()).unwrap();
+        app.handle_activities(0, Ok(vec![commit_at(Utc::now() - TimeDelta::days(2))]));
+        app.handle_activities(0, Err(ActivityError::RateLimited));
+
+        assert!(app.running);
+        assert_eq!(app.states[0].happiness, Happiness::Okayish);
+        assert_eq!(app.states[0].error, Some(ActivityError::RateLimited));
+    }
+
+    #[tokio::test]
+    async fn activities_are_logged_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let source = Source::Git(GitSource {
+            path: "abc/cde/fgh".into(),
+            refs: GitRefs::default(),
+            snacking: false,
+            settings: SourceSettings::default(),
+        });
+        let settings = AppSettings {
+            history_file: Some(HistoryFile::new(path.clone())),
+            ..AppSettings::default()
+        };
+        let mut app = App::new(vec![source.clone()], settings).unwrap();
+        let commit = commit_at(Utc::now() - TimeDelta::hours(1));
+        app.handle_activities(0, Ok(vec![commit.clone()]));
+        app.handle_activities(0, Ok(vec![commit.clone()]));
+        assert_eq!(
+            app.states[0].history.activities(),
+            std::slice::from_ref(&commit)
+        );
+
+        let settings = AppSettings {
+            history_file: Some(HistoryFile::new(path)),
+            ..AppSettings::default()
+        };
+        let app = App::new(vec![source], settings).unwrap();
+        assert_eq!(app.states[0].history.activities(), &[commit]);
+    }
+
+    #[tokio::test]
+    async fn logged_activities_are_filtered_by_the_current_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let source = |settings| {
+            Source::Git(GitSource {
+                path: "abc/cde/fgh".into(),
+                refs: GitRefs::default(),
+                snacking: false,
+                settings,
+            })
+        };
+        let app_settings = || AppSettings {
+            history_file: Some(HistoryFile::new(path.clone())),
+            ..AppSettings::default()
+        };
+        let mut app = App::new(vec![source(SourceSettings::default())], app_settings()).unwrap();
+        let commit = commit_at(Utc::now() - TimeDelta::days(30));
+        let bump = Activity {
+            message: Some("chore: bump".into()),
+            ..commit_at(Utc::now() - TimeDelta::hours(1))
+        };
+        app.handle_activities(0, Ok(vec![commit.clone(), bump]));
+
+        let mut settings = SourceSettings::default();
+        settings.ignore.messages = vec![regex::Regex::new("^chore").unwrap()];
+        let mut app = App::new(vec![source(settings)], app_settings()).unwrap();
+        assert_eq!(
+            app.states[0].history.activities(),
+            std::slice::from_ref(&commit)
+        );
+        app.handle_activities(0, Ok(vec![]));
+        assert_eq!(app.states[0].last_activity, Some(commit.timestamp));
+    }
+
+    #[tokio::test]
+    async fn snacks_keep_the_ferris_okayish_without_being_logged() {
+        let source = Source::Git(GitSource {
+            path: "abc/cde/fgh".into(),
+            refs: GitRefs::default(),
+            snacking: true,
+            settings: SourceSettings::default(),
+        });
+        let mut app = App::new(vec![source], AppSettings::default()).unwrap();
+        let commit = commit_at(Utc::now() - TimeDelta::days(30));
+        let snack = Activity {
+            kind: ActivityKind::Snack,
+            ..commit_at(Utc::now() - TimeDelta::minutes(5))
+        };
+
+        app.handle_activities(0, Ok(vec![commit.clone(), snack.clone()]));
+        assert_eq!(app.states[0].happiness, Happiness::Okayish);
+        assert_eq!(app.states[0].last_activity, Some(commit.timestamp));
+        assert_eq!(app.states[0].snacking, Some(snack.timestamp));
+        assert_eq!(
+            app.states[0].history.activities(),
+            std::slice::from_ref(&commit)
+        );
+
+        let fresh = commit_at(Utc::now() - TimeDelta::hours(1));
+        app.handle_activities(0, Ok(vec![fresh, snack]));
+        assert_eq!(app.states[0].happiness, Happiness::Buzzing);
+
+        let skewed = Activity {
+            kind: ActivityKind::Snack,
+            ..commit_at(Utc::now() + TimeDelta::days(1))
+        };
+        app.handle_activities(0, Ok(vec![commit.clone(), skewed]));
+        assert_eq!(app.states[0].happiness, Happiness::Okayish);
+        assert_eq!(app.states[0].error, None);
+
+        app.handle_activities(0, Ok(vec![commit]));
+        assert_eq!(app.states[0].happiness, Happiness::Sad);
+        assert_eq!(app.states[0].snacking, None);
+    }
+
+    #[tokio::test]
+    async fn stored_states_are_restored_on_startup() {
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        let state_file = StateFile::new(temp_file.path().to_path_buf());
+        let source = Source::Git(GitSource {
+            path: "abc/cde/fgh".into(),
+            refs: GitRefs::default(),
+            snacking: false,
+            settings: SourceSettings::default(),
+        });
+        let last_checked = Utc::now() - TimeDelta::hours(3);
+        state_file
+            .save(HashMap::from([(
+                source.to_string(),
+                StoredState {
+                    last_activity: Some(Utc::now() - TimeDelta::days(2)),
+                    last_checked: Some(last_checked),
+                    error: None,
+                },
+            )]))
+            .unwrap();
+
+        let settings = AppSettings {
+            state_file: Some(state_file),
+            ..AppSettings::default()
+        };
+        let app = App::new(vec![source], settings).unwrap();
+        assert_eq!(app.states[0].happiness, Happiness::Okayish);
+        assert_eq!(app.states[0].last_checked, Some(last_checked));
+        assert!(app.states[0].is_stale(3.0, Utc::now()));
+    }
+
+    #[test]
+    fn state_is_stale_after_missed_checks() {
+        let now = Utc::now();
+        let state = SourceState {
+            last_checked: Some(now - TimeDelta::minutes(2)),
+            ..SourceState::default()
+        };
+        assert!(state.is_stale(5.0, now));
+        assert!(!state.is_stale(60.0, now));
+        assert!(!SourceState::default().is_stale(5.0, now));
+    }
+
+    #[tokio::test]
+    async fn kiosk_cycles_through_sources_not_buzzing() {
+        let sources = vec![
+            Source::Git(GitSource {
+                path: "abc/cde/fgh".into(),
+                refs: GitRefs::default(),
+                snacking: false,
+                settings: SourceSettings::default(),
+            });
+            4
+        ];
+        let settings = AppSettings {
+            kiosk: Some(KioskSettings {
+                interval_secs: 10.0,
+                skip_buzzing: true,
+            }),
+            ..AppSettings::default()
+        };
+        let mut app = App::new(sources, settings).unwrap();
+        app.handle_activities(1, Ok(vec![commit_at(Utc::now())]));
+        app.handle_activities(2, Ok(vec![commit_at(Utc::now() - TimeDelta::days(30))]));
+
+        app.kiosk_tick();
+        assert_eq!(app.selected, 2);
+        app.kiosk_tick();
+        assert_eq!(app.selected, 3);
+        app.kiosk_tick();
+        assert_eq!(app.selected, 0);
+
+        app.kiosk = None;
+        app.kiosk_tick();
+        assert_eq!(app.selected, 1);
+    }
+
+    #[tokio::test]
+    async fn custom_theme_is_selected_and_cycled_through() {
+        let mut custom = Theme::default();
+        custom.name = "custom".into();
+        custom.border = custom.border.fg(ratatui::style::Color::Magenta);
+        let settings = AppSettings {
+            theme: custom.clone(),
+            ..AppSettings::default()
+        };
+        let mut app = App::new(vec![], settings).unwrap();
+        assert_eq!(app.theme(), &custom);
+
+        app.handle_key_events(KeyEvent::from(KeyCode::Char('t')))
+            .unwrap();
+        assert_eq!(app.theme().name, "dark");
+    }
+
+    #[tokio::test]
+    async fn keys_move_the_selection_and_toggle_the_help() {
+        let sources = vec![
+            Source::Git(GitSource {
+                path: "abc/cde/fgh".into(),
+                refs: GitRefs::default(),
+                snacking: false,
+                settings: SourceSettings::default(),
+            });
+            3
+        ];
+        let mut app = App::new(sources, AppSettings::default()).unwrap();
+
+        assert_eq!(app_press(&mut app, KeyCode::Char('k')), 2);
+        assert_eq!(app_press(&mut app, KeyCode::Char('j')), 0);
+        assert_eq!(app_press(&mut app, KeyCode::Char('G')), 2);
+        assert_eq!(app_press(&mut app, KeyCode::Char('g')), 0);
+
+        app.handle_key_events(KeyEvent::from(KeyCode::Char('?')))
+            .unwrap();
+        assert!(app.help);
+        app.handle_key_events(KeyEvent::from(KeyCode::Esc)).unwrap();
+        assert!(!app.help);
+        assert!(app.running);
+    }
+
+    #[tokio::test]
+    async fn clicks_select_sources_and_pet_the_ferris() {
+        let sources = vec![
+            Source::Git(GitSource {
+                path: "abc/cde/fgh".into(),
+                refs: GitRefs::default(),
+                snacking: false,
+                settings: SourceSettings::default(),
+            });
+            3
+        ];
+        let mut app = App::new(sources, AppSettings::default()).unwrap();
+        app.area = Rect::new(0, 0, 100, 40);
+        let mouse = |kind, column, row| MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: crossterm::event::KeyModifiers::NONE,
+        };
+        let click = MouseEventKind::Down(MouseButton::Left);
+
+        // the second line inside the border of the list
+        app.handle_mouse_events(mouse(click, 5, 4));
+        assert_eq!(app.selected, 1);
+        app.handle_mouse_events(mouse(MouseEventKind::ScrollDown, 5, 10));
+        app.handle_mouse_events(mouse(MouseEventKind::ScrollDown, 5, 10));
+        assert_eq!(app.selected, 2);
+        app.handle_mouse_events(mouse(MouseEventKind::ScrollUp, 60, 10));
+        assert_eq!(app.selected, 2);
+
+        // the middle of the ferris in the main pane
+        app.handle_mouse_events(mouse(click, 65, 9));
+        assert_eq!(app.reaction, REACTION_TICKS);
+        app.animation_tick();
+        assert_eq!(app.reaction, REACTION_TICKS - 1);
+
+        app.grid = true;
+        app.handle_mouse_events(mouse(click, 5, 5));
+        assert_eq!(app.selected, 0);
+    }
+
+    fn app_press(app: &mut App, code: KeyCode) -> usize {
+        app.handle_key_events(KeyEvent::from(code)).unwrap();
+        app.selected
+    }
+
+    #[test]
+    fn activity_from_the_future_is_clock_skew() {
+        let future = Utc::now() + TimeDelta::hours(1);
+        let happiness = Happiness::from_last_activity(Some(future), &Thresholds::default());
+        assert_eq!(happiness, Err(ActivityError::ClockSkew));
+    }
+
+    #[test]
+    fn happiness_follows_thresholds() {
+        let thresholds = Thresholds::new(TimeDelta::days(7), TimeDelta::days(60)).unwrap();
+        let last_activity = Some(Utc::now() - TimeDelta::days(30));
+
+        let happiness = Happiness::from_last_activity(last_activity, &thresholds);
+        assert_eq!(happiness, Ok(Happiness::Okayish));
+        let happiness = Happiness::from_last_activity(last_activity, &Thresholds::default());
+        assert_eq!(happiness, Ok(Happiness::Sad));
+    }
+
+    #[test]
+    fn interval_defaults_depend_on_the_source_itself() {
+        let with_pat = Source::GitHub(GitHubSource {
+            owner: "owner_name".into(),
+            repo: "repo_name".into(),
+            pat: Some("xyz".into()),
+            settings: SourceSettings::default(),
+        });
+        let without_pat = Source::GitHub(GitHubSource {
+            owner: "owner_name".into(),
+            repo: "repo_name".into(),
+            pat: None,
+            settings: SourceSettings::default(),
+        });
+        let configured = Source::GitHub(GitHubSource {
+            owner: "owner_name".into(),
+            repo: "repo_name".into(),
+            pat: None,
+            settings: SourceSettings {
+                interval_secs: Some(600.0),
+                ..SourceSettings::default()
+            },
+        });
+
+        assert_eq!(with_pat.interval_secs(), 5.0);
+        assert_eq!(without_pat.interval_secs(), 60.0);
+        assert_eq!(configured.interval_secs(), 600.0);
+    }
+
+    #[test]
+    fn thresholds_must_be_monotonic() {
+        assert!(Thresholds::new(TimeDelta::hours(48), TimeDelta::hours(24)).is_err());
+        assert!(Thresholds::new(TimeDelta::hours(24), TimeDelta::hours(24)).is_err());
+        assert!(Thresholds::new(TimeDelta::zero(), TimeDelta::hours(24)).is_err());
+        assert!(Thresholds::new(TimeDelta::hours(1), TimeDelta::hours(24)).is_ok());
+    }
 }
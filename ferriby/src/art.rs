@@ -0,0 +1,366 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::{app::Happiness, event::MAX_INTERVAL_SECS};
+
+/// The line separating the frames in the files of an art pack.
+const FRAME_SEPARATOR: &str = "---";
+
+/// The biggest frame that still fits the panes and the tiles of the grid.
+pub const MAX_FRAME_WIDTH: usize = 22;
+pub const MAX_FRAME_HEIGHT: usize = 6;
+
+/// The names of the moods, as used for the files and colours of an art pack.
+const MOODS: [(&str, Happiness); 4] = [
+    ("undecided", Happiness::Undecided),
+    ("sad", Happiness::Sad),
+    ("okayish", Happiness::Okayish),
+    ("buzzing", Happiness::Buzzing),
+];
+
+/// The animation of a ferris in one mood.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoodArt {
+    /// The frames, shown one after another.
+    pub frames: Vec<String>,
+    /// The colour of the ferris, if it has its own.
+    pub color: Option<Color>,
+}
+
+impl MoodArt {
+    fn builtin(frames: &[&str]) -> Self {
+        Self {
+            frames: frames.iter().map(|frame| frame.to_string()).collect(),
+            color: None,
+        }
+    }
+}
+
+/// The metadata in the `pack.json` of an art pack.
+#[derive(Debug, Default, Deserialize)]
+struct PackMetadata {
+    name: Option<String>,
+    frame_secs: Option<f32>,
+    #[serde(default)]
+    colors: HashMap<String, String>,
+}
+
+/// The ASCII art of the ferris in all moods.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtPack {
+    /// The name of the pack.
+    pub name: String,
+    /// How long each frame is shown.
+    pub frame_secs: f32,
+    pub undecided: MoodArt,
+    pub sad: MoodArt,
+    pub okayish: MoodArt,
+    pub buzzing: MoodArt,
+}
+
+impl Default for ArtPack {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl ArtPack {
+    /// The art of the ferris in the given mood.
+    pub fn mood(&self, happiness: Happiness) -> &MoodArt {
+        match happiness {
+            Happiness::Undecided => &self.undecided,
+            Happiness::Sad => &self.sad,
+            Happiness::Okayish => &self.okayish,
+            Happiness::Buzzing => &self.buzzing,
+        }
+    }
+
+    fn mood_mut(&mut self, happiness: Happiness) -> &mut MoodArt {
+        match happiness {
+            Happiness::Undecided => &mut self.undecided,
+            Happiness::Sad => &mut self.sad,
+            Happiness::Okayish => &mut self.okayish,
+            Happiness::Buzzing => &mut self.buzzing,
+        }
+    }
+
+    /// The frame of the animation to show for the given mood.
+    pub fn frame(&self, happiness: Happiness, animation: usize) -> &str {
+        let frames = &self.mood(happiness).frames;
+        frames
+            .get(animation % frames.len().max(1))
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    /// Loads the art pack in the given directory.
+    ///
+    /// It holds a file per mood, `undecided.txt`, `sad.txt`, `okayish.txt` and `buzzing.txt`,
+    /// with the frames separated by `---` lines, and an optional `pack.json` with the `name`,
+    /// the `frame_secs` and the `colors` of the moods.
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let read = |file: &str| {
+            let path = dir.join(file);
+            fs::read_to_string(&path).map_err(|e| format!("failed to read {}: {e}", path.display()))
+        };
+        let metadata_path = dir.join("pack.json");
+        let metadata: PackMetadata = if metadata_path.exists() {
+            serde_json::from_str(&read("pack.json")?)
+                .map_err(|e| format!("failed to parse {}: {e}", metadata_path.display()))?
+        } else {
+            PackMetadata::default()
+        };
+
+        let mut pack = Self::builtin();
+        pack.name = match metadata.name {
+            Some(name) => name,
+            None => dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        };
+        if let Some(frame_secs) = metadata.frame_secs {
+            pack.frame_secs = frame_secs;
+        }
+        for (mood, happiness) in MOODS {
+            pack.mood_mut(happiness).frames = parse_frames(&read(&format!("{mood}.txt"))?);
+        }
+        for (mood, color) in metadata.colors {
+            let (_, happiness) = MOODS
+                .iter()
+                .find(|(name, _)| *name == mood)
+                .ok_or_else(|| format!("unknown mood {mood} in the colours of {}", pack.name))?;
+            let color = color
+                .parse()
+                .map_err(|_| format!("invalid colour {color} for {mood} in {}", pack.name))?;
+            pack.mood_mut(*happiness).color = Some(color);
+        }
+
+        let problems = pack.validate();
+        if problems.is_empty() {
+            Ok(pack)
+        } else {
+            Err(format!(
+                "invalid art pack {}: {}",
+                pack.name,
+                problems.join(", ")
+            ))
+        }
+    }
+
+    /// Finds the problems of the pack, like moods without frames, frames of different sizes
+    /// in one mood or frames too big to fit.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = vec![];
+        if !self.frame_secs.is_finite()
+            || self.frame_secs <= 0.0
+            || self.frame_secs > MAX_INTERVAL_SECS
+        {
+            problems.push(format!(
+                "frame_secs must be positive and at most {MAX_INTERVAL_SECS}"
+            ));
+        }
+        for (mood, happiness) in MOODS {
+            let sizes: Vec<(usize, usize)> = self
+                .mood(happiness)
+                .frames
+                .iter()
+                .map(|frame| frame_size(frame))
+                .collect();
+            match sizes.first() {
+                None => problems.push(format!("{mood} has no frames")),
+                Some(first) if sizes.iter().any(|size| size != first) => {
+                    problems.push(format!("{mood} has frames of different sizes"))
+                }
+                _ => {}
+            }
+            if sizes
+                .iter()
+                .any(|(width, height)| *width > MAX_FRAME_WIDTH || *height > MAX_FRAME_HEIGHT)
+            {
+                problems.push(format!(
+                    "{mood} has frames bigger than {MAX_FRAME_WIDTH}x{MAX_FRAME_HEIGHT}"
+                ));
+            }
+        }
+        problems
+    }
+
+    /// The art shipped with ferriby.
+    pub fn builtin() -> Self {
+        Self {
+            name: "ferris".into(),
+            frame_secs: 0.7,
+            undecided: MoodArt::builtin(&[
+                r"
+    _~^~^~_
+   / o  o  \
+  '_       _'
+  \ '-----' /
+",
+                r"
+    _~^~^~_
+   /  o  o \
+  '_       _'
+  \ '-----' /
+",
+            ]),
+            sad: MoodArt::builtin(&[
+                r"
+    _~^~^~_
+\) / .  .  \ (/
+  '_  / \  _'
+  \ '-----' \
+",
+                r"
+    _~^~^~_
+\) /  .  . \ (/
+  '_  / \  _'
+  / '-----' /
+",
+            ]),
+            okayish: MoodArt::builtin(&[
+                r"
+    _~^~^~_
+\) /  o o  \ (/
+  '_   ==  _'
+  \ '-----' /
+",
+                r"
+    _~^~^~_
+\) /  o o  \ (/
+  '_  ==   _'
+  \ '-----' /
+",
+            ]),
+            buzzing: MoodArt::builtin(&[
+                r"
+    _~^~^~_
+\/ /  o O  \ \/
+  '_  \_/  _'
+  \ '-----' /
+",
+                r"
+\/  _~^^^~_  \/
+ \ /  O o  \ /
+  '_  *o*  _'
+  / '-----' \
+",
+                r"
+    _~^~^~_
+\/ /  o O  \ \/
+  '_  \_/  _'
+  \ '-----' /
+",
+                r"
+    _~^~^~_
+\  /  O -  \  /
+  '_  \_/  _'
+  \ '-----' /
+",
+            ]),
+        }
+    }
+}
+
+/// Splits the content of a mood file into its frames.
+fn parse_frames(content: &str) -> Vec<String> {
+    let mut frames = vec![];
+    let mut frame: Vec<&str> = vec![];
+    for line in content.lines() {
+        if line.trim_end() == FRAME_SEPARATOR {
+            frames.push(frame.join("\n"));
+            frame.clear();
+        } else {
+            frame.push(line);
+        }
+    }
+    frames.push(frame.join("\n"));
+    frames.retain(|frame| !frame.trim().is_empty());
+    frames
+}
+
+/// The width and height of a frame, ignoring trailing whitespace.
+pub fn frame_size(frame: &str) -> (usize, usize) {
+    let width = frame
+        .lines()
+        .map(|line| line.trim_end().chars().count())
+        .max()
+        .unwrap_or(0);
+    (width, frame.trim_end().lines().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_pack(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, content) in files {
+            fs::write(dir.path().join(name), content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn builtin_pack_is_valid() {
+        assert_eq!(ArtPack::builtin().validate(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn pack_is_loaded_from_directory() {
+        let dir = write_pack(&[
+            (
+                "pack.json",
+                r#"{ "name": "crab", "frame_secs": 0.5, "colors": { "sad": "blue" } }"#,
+            ),
+            ("undecided.txt", "(?)"),
+            ("sad.txt", "(;_;)\n---\n(T_T)\n"),
+            ("okayish.txt", "(-_-)"),
+            ("buzzing.txt", "(^o^)\n---\n(^_^)\n---\n"),
+        ]);
+        let pack = ArtPack::load(dir.path()).unwrap();
+
+        assert_eq!(pack.name, "crab");
+        assert_eq!(pack.frame_secs, 0.5);
+        assert_eq!(pack.sad.frames, vec!["(;_;)", "(T_T)"]);
+        assert_eq!(pack.sad.color, Some(Color::Blue));
+        assert_eq!(pack.okayish.color, None);
+        assert_eq!(pack.frame(Happiness::Buzzing, 3), "(^_^)");
+    }
+
+    #[test]
+    fn ragged_oversized_or_missing_art_is_reported() {
+        let dir = write_pack(&[
+            ("undecided.txt", "(?)"),
+            ("sad.txt", "(;_;)\n---\n(T_T)\n(T_T)"),
+            ("okayish.txt", &"-".repeat(MAX_FRAME_WIDTH + 1)),
+            ("buzzing.txt", "---\n"),
+        ]);
+        let error = ArtPack::load(dir.path()).unwrap_err();
+
+        assert!(
+            error.contains("sad has frames of different sizes"),
+            "{error}"
+        );
+        assert!(error.contains("okayish has frames bigger than"), "{error}");
+        assert!(error.contains("buzzing has no frames"), "{error}");
+        assert!(!error.contains("undecided"), "{error}");
+
+        let dir = write_pack(&[("sad.txt", "(;_;)")]);
+        assert!(ArtPack::load(dir.path()).is_err());
+    }
+
+    #[test]
+    fn out_of_range_frame_secs_are_reported() {
+        for frame_secs in [0.0, -1.0, 1e30] {
+            let pack = ArtPack {
+                frame_secs,
+                ..ArtPack::builtin()
+            };
+            assert_eq!(pack.validate().len(), 1, "{frame_secs}");
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// Errors that can occur while checking the activity of a source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ActivityError {
+    /// The repository, branch or project doesn't exist.
+    NotFound(String),
+    /// The credentials are missing, invalid or lack permissions.
+    Unauthorized,
+    /// The hoster refuses to answer until the rate limit resets.
+    RateLimited,
+    /// The request failed on the way or with an unexpected status.
+    Network(String),
+    /// The response or a timestamp in it couldn't be parsed.
+    Parse(String),
+    /// The last activity lies in the future.
+    ClockSkew,
+    /// The local repository couldn't be read.
+    Git(String),
+    /// The check itself failed unexpectedly.
+    Internal(String),
+}
+
+impl Display for ActivityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActivityError::NotFound(what) => write!(f, "not found: {what}"),
+            ActivityError::Unauthorized => write!(f, "unauthorized"),
+            ActivityError::RateLimited => write!(f, "rate limited"),
+            ActivityError::Network(e) => write!(f, "network error: {e}"),
+            ActivityError::Parse(e) => write!(f, "parse error: {e}"),
+            ActivityError::ClockSkew => write!(f, "activity from the future"),
+            ActivityError::Git(e) => write!(f, "git error: {e}"),
+            ActivityError::Internal(e) => write!(f, "internal error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ActivityError {}
+
+impl From<git2::Error> for ActivityError {
+    fn from(e: git2::Error) -> Self {
+        match e.code() {
+            git2::ErrorCode::NotFound => ActivityError::NotFound(e.message().to_string()),
+            _ => ActivityError::Git(e.message().to_string()),
+        }
+    }
+}
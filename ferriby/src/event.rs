@@ -2,24 +2,22 @@ use color_eyre::eyre::OptionExt;
 use futures::{FutureExt, StreamExt};
 use ratatui::crossterm::event::Event as CrosstermEvent;
 use std::time::Duration;
-use tokio::{
-    sync::mpsc,
-    task::{JoinHandle, JoinSet},
-};
+use tokio::{sync::mpsc, task::JoinSet};
+
+use crate::{activity::Activity, error::ActivityError};
+
+/// The longest interval in seconds, a week, to keep the timers from overflowing.
+pub const MAX_INTERVAL_SECS: f32 = 7.0 * 24.0 * 3600.0;
 
 /// Representation of all possible events.
 #[derive(Clone, Debug)]
 pub enum Event {
-    /// An event that is emitted when it's time to check git.
-    GitTick,
-    /// An event that is emitted when it's time to check GitHub.
-    GitHubTick,
-    /// An event that is emitted when it's time to check GitLab.
-    GitLabTick,
-    /// An event that is emitted when it's time to check Forgejo.
-    ForgejoTick,
+    /// An event that is emitted when it's time to check the source with the given index.
+    SourceTick(usize),
     /// Event emitted when it's time to animate ferris.
     AnimationTick,
+    /// Event emitted when it's time to show the next source in kiosk mode.
+    KioskTick,
     /// Crossterm events.
     ///
     /// These events are emitted by the terminal.
@@ -37,46 +35,30 @@ pub enum Event {
 pub enum AppEvent {
     /// Quit the application.
     Quit,
-}
-
-/// The intervals of the sources
-#[derive(Clone, Debug, Default)]
-pub struct IntervalSecs {
-    /// The interval for git checks.
-    pub git: Option<f32>,
-    /// The interval for GitHub checks.
-    pub github: Option<f32>,
-    /// The interval for GitLab checks.
-    pub gitlab: Option<f32>,
-    /// The interval for Forgejo checks.
-    pub forgejo: Option<f32>,
+    /// The activities of the source with the given index were checked.
+    Activities(usize, Result<Vec<Activity>, ActivityError>),
 }
 
 /// Terminal event handler.
 #[derive(Debug)]
 pub struct EventHandler {
-    /// The intervals
-    interval_secs: IntervalSecs,
     /// Event sender channel.
     sender: mpsc::UnboundedSender<Event>,
     /// Event receiver channel.
     receiver: mpsc::UnboundedReceiver<Event>,
-    /// The EventTask task
-    actor_task: JoinHandle<Result<(), color_eyre::eyre::Error>>,
 }
 
 impl EventHandler {
     /// Constructs a new instance of [`EventHandler`] and spawns a new thread to handle events.
-    pub fn new(interval_secs: IntervalSecs) -> Self {
+    ///
+    /// `interval_secs` holds the check interval of each source, indexed like the sources of the app.
+    /// `kiosk_secs` is how long to show each source in kiosk mode, if at all.
+    /// `animation_secs` is how long to show each frame of the ferris.
+    pub fn new(interval_secs: Vec<f32>, kiosk_secs: Option<f32>, animation_secs: f32) -> Self {
         let (sender, receiver) = mpsc::unbounded_channel();
-        let actor = EventTask::new(sender.clone(), interval_secs.clone());
-        let actor_task = tokio::spawn(async { actor.run().await });
-        Self {
-            interval_secs,
-            sender,
-            receiver,
-            actor_task,
-        }
+        let actor = EventTask::new(sender.clone(), interval_secs, kiosk_secs, animation_secs);
+        tokio::spawn(async { actor.run().await });
+        Self { sender, receiver }
     }
 
     /// Receives an event from the sender.
@@ -105,18 +87,15 @@ impl EventHandler {
         let _ = self.sender.send(Event::App(app_event));
     }
 
-    /// Restart the EventTask actor to have fast updates after a change of the selected source
-    pub fn restart(&mut self) {
-        self.actor_task.abort();
-        let actor = EventTask::new(self.sender.clone(), self.interval_secs.clone());
-        self.actor_task = tokio::spawn(async { actor.run().await });
+    /// A sender to emit events from background tasks.
+    pub fn sender(&self) -> mpsc::UnboundedSender<Event> {
+        self.sender.clone()
     }
 }
 
 impl Default for EventHandler {
     fn default() -> Self {
-        let intervals = IntervalSecs::default();
-        Self::new(intervals)
+        Self::new(vec![], None, 0.7)
     }
 }
 
@@ -124,15 +103,27 @@ impl Default for EventHandler {
 struct EventTask {
     /// Event sender channel.
     sender: mpsc::UnboundedSender<Event>,
-    interval_secs: IntervalSecs,
+    /// The check interval of each source.
+    interval_secs: Vec<f32>,
+    /// How long to show each source in kiosk mode, if at all.
+    kiosk_secs: Option<f32>,
+    /// How long to show each frame of the ferris.
+    animation_secs: f32,
 }
 
 impl EventTask {
     /// Constructs a new instance of [`EventThread`].
-    fn new(sender: mpsc::UnboundedSender<Event>, interval_secs: IntervalSecs) -> Self {
+    fn new(
+        sender: mpsc::UnboundedSender<Event>,
+        interval_secs: Vec<f32>,
+        kiosk_secs: Option<f32>,
+        animation_secs: f32,
+    ) -> Self {
         Self {
             sender,
             interval_secs,
+            kiosk_secs,
+            animation_secs,
         }
     }
 
@@ -156,46 +147,48 @@ impl EventTask {
         }
     }
 
+    /// Like [`EventTask::tick_thread`], but waits a whole interval before the first tick.
+    async fn delayed_tick_thread(
+        sender: mpsc::UnboundedSender<Event>,
+        event: Event,
+        interval_secs: f32,
+    ) {
+        let tick_rate = Duration::from_secs_f32(interval_secs);
+        let mut tick = tokio::time::interval_at(tokio::time::Instant::now() + tick_rate, tick_rate);
+        loop {
+            let _ = tick.tick().await;
+            let _ = sender.send(event.clone());
+        }
+    }
+
     /// Runs the event thread.
     ///
-    /// This function emits tick events at a fixed rate and polls for crossterm events in between.
+    /// This function emits a tick event per source at the source's rate and polls for crossterm
+    /// events in between.
     async fn run(self) -> color_eyre::Result<()> {
         let mut set = JoinSet::new();
         let keyevent_sender = self.sender.clone();
         set.spawn(async move { EventTask::key_thread(keyevent_sender).await });
 
         let animation_sender = self.sender.clone();
+        let animation_secs = self.animation_secs;
         set.spawn(async move {
-            EventTask::tick_thread(animation_sender, Event::AnimationTick, 0.7).await
+            EventTask::tick_thread(animation_sender, Event::AnimationTick, animation_secs).await
         });
 
-        if let Some(secs) = self.interval_secs.git {
-            let tick_sender = self.sender.clone();
-            set.spawn(
-                async move { EventTask::tick_thread(tick_sender, Event::GitTick, secs).await },
-            );
-        };
-
-        if let Some(secs) = self.interval_secs.github {
-            let tick_sender = self.sender.clone();
-            set.spawn(
-                async move { EventTask::tick_thread(tick_sender, Event::GitHubTick, secs).await },
-            );
-        };
-
-        if let Some(secs) = self.interval_secs.gitlab {
-            let tick_sender = self.sender.clone();
-            set.spawn(
-                async move { EventTask::tick_thread(tick_sender, Event::GitLabTick, secs).await },
-            );
-        };
+        if let Some(secs) = self.kiosk_secs {
+            let kiosk_sender = self.sender.clone();
+            set.spawn(async move {
+                EventTask::delayed_tick_thread(kiosk_sender, Event::KioskTick, secs).await
+            });
+        }
 
-        if let Some(secs) = self.interval_secs.forgejo {
+        for (index, secs) in self.interval_secs.into_iter().enumerate() {
             let tick_sender = self.sender.clone();
             set.spawn(async move {
-                EventTask::tick_thread(tick_sender, Event::ForgejoTick, secs).await
+                EventTask::tick_thread(tick_sender, Event::SourceTick(index), secs).await
             });
-        };
+        }
 
         let _ = set.join_all().await;
         Ok(())
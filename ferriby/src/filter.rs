@@ -0,0 +1,294 @@
+use std::str::FromStr;
+
+use regex::Regex;
+
+use crate::activity::{Activity, ActivityKind};
+
+/// Does `text` match the glob `pattern`, in which `*` matches any characters, including `/`,
+/// and `?` matches a single one?
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // where the last `*` was seen and the text position it matched up to
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                // let the last `*` match one more character
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// The names a ref can be matched by: the full name like `refs/remotes/origin/main`, the short
+/// name like `origin/main` and, for remote-tracking branches, the name of the branch on the
+/// remote like `main`.
+fn ref_names(git_ref: &str) -> Vec<&str> {
+    let mut names = vec![git_ref];
+    for prefix in ["refs/heads/", "refs/tags/", "refs/notes/"] {
+        if let Some(short) = git_ref.strip_prefix(prefix) {
+            names.push(short);
+        }
+    }
+    if let Some(short) = git_ref.strip_prefix("refs/remotes/") {
+        names.push(short);
+        if let Some((_, branch)) = short.split_once('/') {
+            names.push(branch);
+        }
+    }
+    names
+}
+
+/// Which branches and other refs count for a source, by glob patterns like `dependabot/*`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RefFilter {
+    /// The refs to look at, all if empty.
+    pub include: Vec<String>,
+    /// The refs to ignore, even if included.
+    pub exclude: Vec<String>,
+}
+
+impl RefFilter {
+    /// Does the ref with the given name count?
+    pub fn matches(&self, git_ref: &str) -> bool {
+        let names = ref_names(git_ref);
+        let matches_any = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| names.iter().any(|name| glob_match(pattern, name)))
+        };
+        (self.include.is_empty() || matches_any(&self.include)) && !matches_any(&self.exclude)
+    }
+
+    /// Does the activity count? Activities without a ref always do.
+    pub fn allows(&self, activity: &Activity) -> bool {
+        activity
+            .git_ref
+            .as_deref()
+            .is_none_or(|git_ref| self.matches(git_ref))
+    }
+}
+
+/// A pattern matching who did an activity.
+#[derive(Debug, Clone)]
+pub enum AuthorPattern {
+    /// A name, email address or username, compared case-insensitively.
+    Exact(String),
+    /// A regular expression, written as `/regex/`.
+    Regex(Regex),
+}
+
+impl PartialEq for AuthorPattern {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (AuthorPattern::Exact(a), AuthorPattern::Exact(b)) => a == b,
+            (AuthorPattern::Regex(a), AuthorPattern::Regex(b)) => a.as_str() == b.as_str(),
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for AuthorPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix('/').and_then(|s| s.strip_suffix('/')) {
+            Some(regex) => Regex::new(regex)
+                .map(AuthorPattern::Regex)
+                .map_err(|e| format!("invalid author regex {s}: {e}")),
+            None => Ok(AuthorPattern::Exact(s.to_string())),
+        }
+    }
+}
+
+impl AuthorPattern {
+    /// Does the pattern match the given name, email address or username?
+    pub fn matches(&self, author: &str) -> bool {
+        match self {
+            AuthorPattern::Exact(name) => name.to_lowercase() == author.to_lowercase(),
+            AuthorPattern::Regex(regex) => regex.is_match(author),
+        }
+    }
+}
+
+/// Whose activities count for a source, everyone's if there are no patterns.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuthorFilter {
+    pub patterns: Vec<AuthorPattern>,
+}
+
+impl AuthorFilter {
+    /// Does the activity count? Activities of unknown authors only do without patterns.
+    pub fn allows(&self, activity: &Activity) -> bool {
+        self.patterns.is_empty()
+            || [&activity.actor, &activity.email]
+                .into_iter()
+                .flatten()
+                .any(|author| self.patterns.iter().any(|pattern| pattern.matches(author)))
+    }
+}
+
+/// Which activities don't count for a source, like the ones of bots or merges.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    /// Ignore merges, of commits as well as of pull requests.
+    pub merges: bool,
+    /// Ignore commits that don't change anything, only known for local repositories.
+    pub empty: bool,
+    /// Ignore the activities of these authors.
+    pub bots: Vec<AuthorPattern>,
+    /// Ignore the activities with messages matching any of these, like `^(fixup|squash)!`.
+    pub messages: Vec<Regex>,
+}
+
+impl PartialEq for IgnoreRules {
+    fn eq(&self, other: &Self) -> bool {
+        let regexes = |rules: &Self| -> Vec<String> {
+            rules
+                .messages
+                .iter()
+                .map(|regex| regex.as_str().to_string())
+                .collect()
+        };
+        self.merges == other.merges
+            && self.empty == other.empty
+            && self.bots == other.bots
+            && regexes(self) == regexes(other)
+    }
+}
+
+impl IgnoreRules {
+    /// Does the activity count?
+    pub fn allows(&self, activity: &Activity) -> bool {
+        let merge = self.merges && activity.kind == ActivityKind::Merge;
+        let bot = [&activity.actor, &activity.email]
+            .into_iter()
+            .flatten()
+            .any(|author| self.bots.iter().any(|pattern| pattern.matches(author)));
+        let message = activity
+            .message
+            .as_deref()
+            .is_some_and(|message| self.messages.iter().any(|regex| regex.is_match(message)));
+        !merge && !bot && !message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs_match_any_characters() {
+        assert!(glob_match("dependabot/*", "dependabot/cargo/serde-1.0"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("release-?.*", "release-1.2"));
+        assert!(glob_match("*bot*", "renovate-bot/x"));
+        assert!(!glob_match("dependabot/*", "main"));
+        assert!(!glob_match("release-?", "release-10"));
+        assert!(!glob_match("main", "main2"));
+    }
+
+    #[test]
+    fn authors_are_matched_by_name_email_or_regex() {
+        let activity = |actor: Option<&str>, email: Option<&str>| Activity {
+            timestamp: chrono::Utc::now(),
+            kind: crate::activity::ActivityKind::Commit,
+            actor: actor.map(String::from),
+            email: email.map(String::from),
+            git_ref: None,
+            message: None,
+        };
+        let filter = AuthorFilter {
+            patterns: ["Dawe", "me@example.org", "/@team\\.example\\.org$/"]
+                .iter()
+                .map(|pattern| pattern.parse().unwrap())
+                .collect(),
+        };
+
+        assert!(filter.allows(&activity(Some("dawe"), None)));
+        assert!(filter.allows(&activity(Some("Me"), Some("ME@example.org"))));
+        assert!(filter.allows(&activity(None, Some("kim@team.example.org"))));
+        assert!(!filter.allows(&activity(Some("dependabot[bot]"), None)));
+        assert!(!filter.allows(&activity(None, None)));
+        assert!(AuthorFilter::default().allows(&activity(None, None)));
+        assert!("/(/".parse::<AuthorPattern>().is_err());
+    }
+
+    #[test]
+    fn bots_merges_and_fixups_are_ignored() {
+        let rules = IgnoreRules {
+            merges: true,
+            empty: true,
+            bots: vec!["/\\[bot\\]$/".parse().unwrap()],
+            messages: vec![Regex::new("^(fixup|squash)!").unwrap()],
+        };
+        let activity = |kind, actor: &str, message: &str| Activity {
+            timestamp: chrono::Utc::now(),
+            kind,
+            actor: Some(actor.into()),
+            email: None,
+            git_ref: None,
+            message: Some(message.into()),
+        };
+
+        assert!(rules.allows(&activity(ActivityKind::Commit, "dawe", "Add grid")));
+        assert!(!rules.allows(&activity(
+            ActivityKind::Merge,
+            "dawe",
+            "Merge branch 'main'"
+        )));
+        assert!(!rules.allows(&activity(
+            ActivityKind::Push,
+            "dependabot[bot]",
+            "Bump serde"
+        )));
+        assert!(!rules.allows(&activity(ActivityKind::Commit, "dawe", "fixup! Add grid")));
+        assert!(IgnoreRules::default().allows(&activity(ActivityKind::Merge, "x[bot]", "")));
+    }
+
+    #[test]
+    fn refs_are_matched_by_full_and_short_names() {
+        let filter = RefFilter {
+            include: vec![],
+            exclude: vec!["dependabot/*".into(), "renovate/*".into()],
+        };
+        assert!(filter.matches("refs/heads/main"));
+        assert!(filter.matches("main"));
+        assert!(!filter.matches("refs/heads/dependabot/cargo/serde"));
+        assert!(!filter.matches("renovate/tokio"));
+        assert!(!filter.matches("refs/remotes/origin/dependabot/cargo/serde"));
+
+        let filter = RefFilter {
+            include: vec!["main".into(), "release/*".into()],
+            exclude: vec!["release/old".into()],
+        };
+        assert!(filter.matches("refs/heads/main"));
+        assert!(filter.matches("refs/remotes/origin/release/2.0"));
+        assert!(!filter.matches("refs/heads/release/old"));
+        assert!(!filter.matches("refs/heads/feature"));
+
+        let filter = RefFilter {
+            include: vec!["refs/remotes/origin/*".into()],
+            exclude: vec![],
+        };
+        assert!(filter.matches("refs/remotes/origin/main"));
+        assert!(!filter.matches("refs/remotes/upstream/main"));
+        assert!(!filter.matches("refs/heads/main"));
+    }
+}
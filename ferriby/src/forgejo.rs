@@ -1,13 +1,12 @@
-use std::cell::LazyCell;
-
-use chrono::NaiveDateTime;
 use chrono::{DateTime, offset::Utc};
 use http::{HeaderMap, header};
-use regex::Regex;
 use reqwest::Url;
+use serde::Deserialize;
 
-use crate::app::ActivitySource;
-use crate::githoster::get_with_headers;
+use crate::activity::{Activity, ActivityKind};
+use crate::app::{ActivitySource, SourceSettings};
+use crate::error::ActivityError;
+use crate::githoster::GitHosterClient;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ForgejoSource {
@@ -15,14 +14,25 @@ pub struct ForgejoSource {
     pub owner: String,
     pub repo: String,
     pub pat: Option<String>,
+    pub settings: SourceSettings,
 }
 
 impl ActivitySource for ForgejoSource {
-    async fn get_last_activity(self) -> Option<DateTime<Utc>> {
+    async fn get_activities(
+        self,
+        client: GitHosterClient,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Activity>, ActivityError> {
         let url = self
             .base_url
-            .join(format!("api/v1/repos/{}/{}", self.owner, self.repo).as_str())
-            .unwrap();
+            .join(
+                format!(
+                    "api/v1/repos/{}/{}/activities/feeds?limit=50",
+                    self.owner, self.repo
+                )
+                .as_str(),
+            )
+            .map_err(|e| ActivityError::Parse(e.to_string()))?;
 
         let mut headers: HeaderMap = HeaderMap::new();
         headers.insert(
@@ -35,54 +45,82 @@ impl ActivitySource for ForgejoSource {
         );
         if let Some(token) = &self.pat {
             let pat = header::HeaderValue::from_str(format!("token {token}").as_str())
-                .expect("bad forgejo pat");
+                .map_err(|_| ActivityError::Unauthorized)?;
             headers.insert(header::AUTHORIZATION, pat);
         }
 
-        match get_with_headers(url, headers).await {
-            Some(body) => {
-                let timestamps = ForgejoSource::parse_timestamps(body.as_str());
-                timestamps.into_iter().max()
-            }
-            None => None,
-        }
+        client
+            .get_activities_since(
+                url,
+                headers,
+                &self.settings.connection,
+                since,
+                ForgejoSource::parse_activities,
+            )
+            .await
     }
 }
 
-impl ForgejoSource {
-    // forgejo on sqlite:     "updated_at":"2025-08-04T20:26:36Z",
-    // forgejo on postgres:  "updated_at":"2025-08-09T11:51:12+02:00"
-    fn parse_timestamps(response: &str) -> Vec<DateTime<Utc>> {
-        fn parse_timestamp_str(s: &str, is_utc: bool) -> DateTime<Utc> {
-            if is_utc {
-                let dt = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%SZ")
-                    .expect("unexpected timestamp format");
-                let secs = dt.and_utc().timestamp();
-                DateTime::from_timestamp(secs, 0).expect("from_timestamp failed")
-            } else {
-                let dt = chrono::DateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%z")
-                    .expect("unexpected timestamp format");
-                let secs = dt.timestamp();
-                DateTime::from_timestamp(secs, 0).expect("from_timestamp failed")
-            }
+/// An entry of the `/repos/{owner}/{repo}/activities/feeds` response.
+#[derive(Debug, Deserialize)]
+struct ForgejoActivity {
+    op_type: String,
+    act_user: Option<ForgejoUser>,
+    ref_name: Option<String>,
+    content: Option<String>,
+    // forgejo on sqlite:    "created":"2025-08-04T20:26:36Z"
+    // forgejo on postgres:  "created":"2025-08-09T11:51:12+02:00"
+    created: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ForgejoUser {
+    login: String,
+}
+
+/// The JSON encoded `content` of a push activity.
+#[derive(Debug, Deserialize)]
+struct ForgejoPushCommits {
+    #[serde(rename = "HeadCommit")]
+    head_commit: Option<ForgejoCommit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ForgejoCommit {
+    #[serde(rename = "Message")]
+    message: String,
+}
+
+impl From<ForgejoActivity> for Activity {
+    fn from(activity: ForgejoActivity) -> Self {
+        let kind = match activity.op_type.as_str() {
+            "commit_repo" | "push_tag" | "mirror_sync_push" => ActivityKind::Push,
+            "create_repo" | "mirror_sync_create" => ActivityKind::Create,
+            "delete_branch" | "delete_tag" | "mirror_sync_delete" => ActivityKind::Delete,
+            "merge_pull_request" | "auto_merge_pull_request" => ActivityKind::Merge,
+            other => ActivityKind::Other(other.to_string()),
+        };
+        let message = activity
+            .content
+            .and_then(|content| serde_json::from_str::<ForgejoPushCommits>(&content).ok())
+            .and_then(|commits| commits.head_commit)
+            .and_then(|commit| commit.message.lines().next().map(String::from));
+        Activity {
+            timestamp: activity.created,
+            kind,
+            actor: activity.act_user.map(|user| user.login),
+            email: None,
+            git_ref: activity.ref_name.filter(|ref_name| !ref_name.is_empty()),
+            message,
         }
+    }
+}
 
-        let re: LazyCell<Regex> = LazyCell::new(|| {
-            Regex::new("\"updated_at\":\"(\\d\\d\\d\\d-\\d\\d-\\d\\dT\\d\\d:\\d\\d:\\d\\dZ)\"|\"updated_at\":\"(\\d\\d\\d\\d-\\d\\d-\\d\\dT\\d\\d:\\d\\d:\\d\\d[+-]\\d\\d:\\d\\d)\"")
-                .unwrap()
-        });
-
-        re.captures_iter(response)
-            .map(|m| {
-                if let Some(s) = m.get(1) {
-                    parse_timestamp_str(s.as_str(), true)
-                } else if let Some(s) = m.get(2) {
-                    parse_timestamp_str(s.as_str(), false)
-                } else {
-                    panic!("No matching timestamp group found")
-                }
-            })
-            .collect()
+impl ForgejoSource {
+    fn parse_activities(response: &str) -> Result<Vec<Activity>, ActivityError> {
+        let activities: Vec<ForgejoActivity> =
+            serde_json::from_str(response).map_err(|e| ActivityError::Parse(e.to_string()))?;
+        Ok(activities.into_iter().map(Activity::from).collect())
     }
 }
 
@@ -92,72 +130,108 @@ mod tests {
 
     use super::*;
 
+    fn feed(created: [&str; 2]) -> String {
+        format!(
+            r#"[
+                {{
+                    "id": 1,
+                    "op_type": "commit_repo",
+                    "act_user": {{ "login": "dawe" }},
+                    "ref_name": "refs/heads/main",
+                    "content": "{{\"Commits\":[],\"HeadCommit\":{{\"Message\":\"Fix it\\nfor real\"}}}}",
+                    "created": "{}"
+                }},
+                {{
+                    "id": 2,
+                    "op_type": "merge_pull_request",
+                    "act_user": {{ "login": "dawe" }},
+                    "ref_name": "",
+                    "content": "1|Add forgejo support",
+                    "created": "{}"
+                }}
+            ]"#,
+            created[0], created[1]
+        )
+    }
+
     #[test]
     fn forgejo_parse_positive_offset() {
-        let s = "\"updated_at\":\"2025-07-11T12:30:20+02:00\" bla foo\
-            \"updated_at\":\"2025-07-11T13:31:22+02:00\"";
-        let parsed = ForgejoSource::parse_timestamps(s);
+        let s = feed(["2025-07-11T12:30:20+02:00", "2025-07-11T13:31:22+02:00"]);
+        let parsed = ForgejoSource::parse_activities(&s).unwrap();
 
         assert_eq!(parsed.len(), 2);
 
-        assert_eq!(parsed[0].year(), 2025);
-        assert_eq!(parsed[0].month(), 7);
-        assert_eq!(parsed[0].day(), 11);
-        assert_eq!(parsed[0].hour(), 10);
-        assert_eq!(parsed[0].minute(), 30);
-        assert_eq!(parsed[0].second(), 20);
-
-        assert_eq!(parsed[1].year(), 2025);
-        assert_eq!(parsed[1].month(), 7);
-        assert_eq!(parsed[1].day(), 11);
-        assert_eq!(parsed[1].hour(), 11);
-        assert_eq!(parsed[1].minute(), 31);
-        assert_eq!(parsed[1].second(), 22);
+        assert_eq!(parsed[0].timestamp.year(), 2025);
+        assert_eq!(parsed[0].timestamp.month(), 7);
+        assert_eq!(parsed[0].timestamp.day(), 11);
+        assert_eq!(parsed[0].timestamp.hour(), 10);
+        assert_eq!(parsed[0].timestamp.minute(), 30);
+        assert_eq!(parsed[0].timestamp.second(), 20);
+
+        assert_eq!(parsed[1].timestamp.year(), 2025);
+        assert_eq!(parsed[1].timestamp.month(), 7);
+        assert_eq!(parsed[1].timestamp.day(), 11);
+        assert_eq!(parsed[1].timestamp.hour(), 11);
+        assert_eq!(parsed[1].timestamp.minute(), 31);
+        assert_eq!(parsed[1].timestamp.second(), 22);
     }
 
     #[test]
     fn forgejo_parse_negative_offset() {
-        let s = "\"updated_at\":\"2025-07-11T12:30:20-02:00\" bla foo\
-            \"updated_at\":\"2025-07-11T13:31:22-02:00\"";
-        let parsed = ForgejoSource::parse_timestamps(s);
+        let s = feed(["2025-07-11T12:30:20-02:00", "2025-07-11T13:31:22-02:00"]);
+        let parsed = ForgejoSource::parse_activities(&s).unwrap();
 
         assert_eq!(parsed.len(), 2);
 
-        assert_eq!(parsed[0].year(), 2025);
-        assert_eq!(parsed[0].month(), 7);
-        assert_eq!(parsed[0].day(), 11);
-        assert_eq!(parsed[0].hour(), 14);
-        assert_eq!(parsed[0].minute(), 30);
-        assert_eq!(parsed[0].second(), 20);
-
-        assert_eq!(parsed[1].year(), 2025);
-        assert_eq!(parsed[1].month(), 7);
-        assert_eq!(parsed[1].day(), 11);
-        assert_eq!(parsed[1].hour(), 15);
-        assert_eq!(parsed[1].minute(), 31);
-        assert_eq!(parsed[1].second(), 22);
+        assert_eq!(parsed[0].timestamp.year(), 2025);
+        assert_eq!(parsed[0].timestamp.month(), 7);
+        assert_eq!(parsed[0].timestamp.day(), 11);
+        assert_eq!(parsed[0].timestamp.hour(), 14);
+        assert_eq!(parsed[0].timestamp.minute(), 30);
+        assert_eq!(parsed[0].timestamp.second(), 20);
+
+        assert_eq!(parsed[1].timestamp.year(), 2025);
+        assert_eq!(parsed[1].timestamp.month(), 7);
+        assert_eq!(parsed[1].timestamp.day(), 11);
+        assert_eq!(parsed[1].timestamp.hour(), 15);
+        assert_eq!(parsed[1].timestamp.minute(), 31);
+        assert_eq!(parsed[1].timestamp.second(), 22);
     }
 
     #[test]
     fn forgejo_parse_mixed_tz_info() {
-        let s = "\"updated_at\":\"2025-08-04T20:26:36Z\" bla foo\
-            \"updated_at\":\"2025-07-11T13:31:22-02:00\"";
-        let parsed = ForgejoSource::parse_timestamps(s);
+        let s = feed(["2025-08-04T20:26:36Z", "2025-07-11T13:31:22-02:00"]);
+        let parsed = ForgejoSource::parse_activities(&s).unwrap();
 
         assert_eq!(parsed.len(), 2);
 
-        assert_eq!(parsed[0].year(), 2025);
-        assert_eq!(parsed[0].month(), 8);
-        assert_eq!(parsed[0].day(), 4);
-        assert_eq!(parsed[0].hour(), 20);
-        assert_eq!(parsed[0].minute(), 26);
-        assert_eq!(parsed[0].second(), 36);
-
-        assert_eq!(parsed[1].year(), 2025);
-        assert_eq!(parsed[1].month(), 7);
-        assert_eq!(parsed[1].day(), 11);
-        assert_eq!(parsed[1].hour(), 15);
-        assert_eq!(parsed[1].minute(), 31);
-        assert_eq!(parsed[1].second(), 22);
+        assert_eq!(parsed[0].timestamp.year(), 2025);
+        assert_eq!(parsed[0].timestamp.month(), 8);
+        assert_eq!(parsed[0].timestamp.day(), 4);
+        assert_eq!(parsed[0].timestamp.hour(), 20);
+        assert_eq!(parsed[0].timestamp.minute(), 26);
+        assert_eq!(parsed[0].timestamp.second(), 36);
+
+        assert_eq!(parsed[1].timestamp.year(), 2025);
+        assert_eq!(parsed[1].timestamp.month(), 7);
+        assert_eq!(parsed[1].timestamp.day(), 11);
+        assert_eq!(parsed[1].timestamp.hour(), 15);
+        assert_eq!(parsed[1].timestamp.minute(), 31);
+        assert_eq!(parsed[1].timestamp.second(), 22);
+    }
+
+    #[test]
+    fn forgejo_parse_details() {
+        let s = feed(["2025-08-04T20:26:36Z", "2025-07-11T13:31:22-02:00"]);
+        let parsed = ForgejoSource::parse_activities(&s).unwrap();
+
+        assert_eq!(parsed[0].kind, ActivityKind::Push);
+        assert_eq!(parsed[0].actor.as_deref(), Some("dawe"));
+        assert_eq!(parsed[0].git_ref.as_deref(), Some("refs/heads/main"));
+        assert_eq!(parsed[0].message.as_deref(), Some("Fix it"));
+
+        assert_eq!(parsed[1].kind, ActivityKind::Merge);
+        assert_eq!(parsed[1].git_ref, None);
+        assert_eq!(parsed[1].message, None);
     }
 }
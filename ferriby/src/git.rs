@@ -1,58 +1,780 @@
-use chrono::{DateTime, offset::Utc};
-use git2::{BranchType, Repository, RepositoryOpenFlags};
+use std::{collections::HashSet, fs};
 
-use crate::app::ActivitySource;
+use chrono::{DateTime, Utc};
+use git2::{Commit, Mailmap, Reference, Repository, RepositoryOpenFlags, Sort, StatusOptions};
+
+use crate::activity::{Activity, ActivityKind};
+use crate::app::{ActivitySource, SourceSettings};
+use crate::error::ActivityError;
+use crate::githoster::GitHosterClient;
+
+/// How many commits to walk at most when looking for the rhythm of a repository.
+const MAX_WALKED_COMMITS: usize = 10_000;
+
+/// The activity of making the given commit, by the author as mapped by the `.mailmap`.
+fn commit_activity(
+    commit: &Commit,
+    git_ref: Option<String>,
+    mailmap: &Mailmap,
+) -> Result<Activity, ActivityError> {
+    let secs_since_epoch = commit.time().seconds();
+    let author = commit.author_with_mailmap(mailmap)?;
+    let kind = if commit.parent_count() > 1 {
+        ActivityKind::Merge
+    } else {
+        ActivityKind::Commit
+    };
+    match DateTime::from_timestamp(secs_since_epoch, 0) {
+        Some(timestamp) => Ok(Activity {
+            timestamp,
+            kind,
+            actor: author.name().map(String::from),
+            email: author.email().map(String::from),
+            git_ref,
+            message: commit.summary().map(String::from),
+        }),
+        None => Err(ActivityError::Parse(format!(
+            "invalid commit time {secs_since_epoch}"
+        ))),
+    }
+}
+
+/// Does the commit leave the tree of its only parent, or the empty tree without a parent, as
+/// it is?
+fn is_empty_commit(commit: &Commit) -> bool {
+    match commit.parent_count() {
+        0 => commit.tree().is_ok_and(|tree| tree.is_empty()),
+        1 => commit
+            .parent(0)
+            .is_ok_and(|parent| parent.tree_id() == commit.tree_id()),
+        _ => false,
+    }
+}
+
+/// The activity of editing the uncommitted changes in the working tree, at the newest
+/// modification time of the changed files, if there are any.
+///
+/// Untracked directories aren't looked into, as they may hold lots of build output that isn't
+/// ignored, so they only count by their own modification time.
+fn snack_activity(repo: &Repository, mailmap: &Mailmap) -> Result<Option<Activity>, ActivityError> {
+    let Some(workdir) = repo.workdir() else {
+        return Ok(None);
+    };
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(false)
+        .include_ignored(false)
+        .exclude_submodules(true);
+    let statuses = repo.statuses(Some(&mut options))?;
+    // deleted files have no modification time, but they are rarely all that changed
+    let Some(modified) = statuses
+        .iter()
+        .filter_map(|entry| entry.path().map(|path| workdir.join(path)))
+        .filter_map(|path| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+        .max()
+    else {
+        return Ok(None);
+    };
+    // the one editing the working tree is the user of the repository
+    let user = repo
+        .signature()
+        .and_then(|signature| mailmap.resolve_signature(&signature))
+        .ok();
+    Ok(Some(Activity {
+        timestamp: DateTime::<Utc>::from(modified),
+        kind: ActivityKind::Snack,
+        actor: user.as_ref().and_then(|user| user.name()).map(String::from),
+        email: user
+            .as_ref()
+            .and_then(|user| user.email())
+            .map(String::from),
+        git_ref: None,
+        message: Some(match statuses.len() {
+            1 => "1 uncommitted change".into(),
+            changes => format!("{changes} uncommitted changes"),
+        }),
+    }))
+}
+
+/// The kinds of refs whose activity feeds the ferris of a git source.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RefKind {
+    /// The local branches, `refs/heads/*`.
+    Local,
+    /// The remote-tracking branches, `refs/remotes/*`.
+    Remote,
+    /// The tags, `refs/tags/*`.
+    Tags,
+    /// The notes, `refs/notes/*`.
+    Notes,
+}
+
+impl RefKind {
+    /// The kind with the given name in the config file.
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "local" => Some(RefKind::Local),
+            "remote" => Some(RefKind::Remote),
+            "tags" => Some(RefKind::Tags),
+            "notes" => Some(RefKind::Notes),
+            _ => None,
+        }
+    }
+
+    /// The kind of the ref with the given full name, like `refs/heads/main`.
+    fn of(name: &str) -> Option<Self> {
+        match name.split('/').nth(1)? {
+            "heads" => Some(RefKind::Local),
+            "remotes" => Some(RefKind::Remote),
+            "tags" => Some(RefKind::Tags),
+            "notes" => Some(RefKind::Notes),
+            _ => None,
+        }
+    }
+}
+
+/// When an annotated tag happened.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum TagTime {
+    /// When the tag was created.
+    #[default]
+    Tagger,
+    /// When the tagged commit was made.
+    Commit,
+}
+
+/// Which refs of a git source are looked at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitRefs {
+    pub kinds: Vec<RefKind>,
+    pub tag_time: TagTime,
+}
+
+impl Default for GitRefs {
+    fn default() -> Self {
+        Self {
+            kinds: vec![RefKind::Local],
+            tag_time: TagTime::default(),
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct GitSource {
     pub path: String,
+    pub refs: GitRefs,
+    /// Do uncommitted changes in the working tree count as snacks?
+    pub snacking: bool,
+    pub settings: SourceSettings,
+}
+
+impl GitSource {
+    /// The activity of the tip of the given ref, if it has one.
+    fn ref_activity(
+        &self,
+        repo: &Repository,
+        reference: &Reference,
+        kind: RefKind,
+        mailmap: &Mailmap,
+    ) -> Result<Option<Activity>, ActivityError> {
+        // the full name, as the branches of the source are matched by it
+        let (Some(target), Some(name)) = (reference.target(), reference.name()) else {
+            // symbolic refs like origin/HEAD point to refs that are looked at anyway
+            return Ok(None);
+        };
+        let name = name.to_string();
+        match kind {
+            RefKind::Local | RefKind::Remote => {
+                let commit = repo.find_commit(target)?;
+                if self.settings.ignore.empty && is_empty_commit(&commit) {
+                    return Ok(None);
+                }
+                commit_activity(&commit, Some(name), mailmap).map(Some)
+            }
+            RefKind::Notes => {
+                let mut activity =
+                    commit_activity(&repo.find_commit(target)?, Some(name), mailmap)?;
+                activity.kind = ActivityKind::Note;
+                Ok(Some(activity))
+            }
+            RefKind::Tags => {
+                if let Ok(tag) = repo.find_tag(target)
+                    && let Some(tagger) = tag.tagger()
+                    && self.refs.tag_time == TagTime::Tagger
+                {
+                    let tagger = mailmap.resolve_signature(&tagger)?;
+                    let secs_since_epoch = tagger.when().seconds();
+                    let timestamp =
+                        DateTime::from_timestamp(secs_since_epoch, 0).ok_or_else(|| {
+                            ActivityError::Parse(format!("invalid tag time {secs_since_epoch}"))
+                        })?;
+                    return Ok(Some(Activity {
+                        timestamp,
+                        kind: ActivityKind::Create,
+                        actor: tagger.name().map(String::from),
+                        email: tagger.email().map(String::from),
+                        git_ref: Some(name),
+                        message: tag
+                            .message()
+                            .and_then(|message| message.lines().next())
+                            .map(String::from),
+                    }));
+                }
+                // lightweight tags only have the time of their commit
+                match reference.peel_to_commit() {
+                    Ok(commit) => commit_activity(&commit, Some(name), mailmap).map(Some),
+                    // tags of trees or blobs don't say anything about the activity
+                    Err(_) => Ok(None),
+                }
+            }
+        }
+    }
 }
 
 impl ActivitySource for GitSource {
-    async fn get_last_activity(self) -> Option<DateTime<Utc>> {
-        let repo = match Repository::open_ext(
+    async fn get_activities(
+        self,
+        _client: GitHosterClient,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Activity>, ActivityError> {
+        self.get_activities_blocking(since)
+    }
+}
+
+impl GitSource {
+    /// Like [`ActivitySource::get_activities`], but as git2 only blocks, this is meant to be
+    /// called on the blocking thread pool.
+    pub fn get_activities_blocking(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Activity>, ActivityError> {
+        let repo = Repository::open_ext(
             &self.path,
             RepositoryOpenFlags::CROSS_FS,
             &[] as &[&std::ffi::OsStr],
-        ) {
-            Ok(repo) => repo,
-            Err(e) => panic!("failed to open git repository: {e}"),
-        };
+        )
+        .map_err(|e| match e.code() {
+            git2::ErrorCode::NotFound => ActivityError::NotFound(self.path.clone()),
+            _ => ActivityError::from(e),
+        })?;
 
-        let mut branch_names = vec![];
+        let mailmap = repo.mailmap()?;
+        let mut activities = vec![];
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(Sort::TIME)?;
+        let mut tips = HashSet::new();
 
-        match repo.branches(Some(BranchType::Local)) {
-            Ok(branches) => branches.for_each(|b| {
-                if let Ok(b) = b
-                    && let Ok(Some(branch_name)) = b.0.name()
-                {
-                    branch_names.push(branch_name.to_string())
-                }
-            }),
-            Err(e) => panic!("failed to get branches {e}"),
-        };
+        for reference in repo.references()? {
+            let reference = reference?;
+            let Some(name) = reference
+                .name()
+                .filter(|name| self.settings.branches.matches(name))
+            else {
+                continue;
+            };
+            let Some(kind) = RefKind::of(name).filter(|kind| self.refs.kinds.contains(kind)) else {
+                continue;
+            };
+            if let Some(activity) = self.ref_activity(&repo, &reference, kind, &mailmap)? {
+                activities.push(activity);
+            }
+            // only the history of branches makes up the rhythm
+            if let (RefKind::Local | RefKind::Remote, Some(target)) = (kind, reference.target()) {
+                revwalk.push(target)?;
+                tips.insert(target);
+            }
+        }
 
-        let mut branch_times = vec![];
+        // the commits below the tips make up the rhythm of the repository
+        let since = since.timestamp();
+        let mut fed = activities
+            .iter()
+            .any(|activity| self.settings.allows(activity));
+        for oid in revwalk.take(MAX_WALKED_COMMITS) {
+            let oid = oid?;
+            if tips.contains(&oid) {
+                continue;
+            }
+            let commit = repo.find_commit(oid)?;
+            let before_since = commit.time().seconds() < since;
+            if before_since && fed {
+                break;
+            }
+            if self.settings.ignore.empty && is_empty_commit(&commit) {
+                continue;
+            }
+            let activity = commit_activity(&commit, None, &mailmap)?;
+            let counts = self.settings.allows(&activity);
+            // before since, only the newest commit that counts is of interest, to tell since
+            // when the ferris is hungry
+            if before_since && !counts {
+                continue;
+            }
+            fed |= counts;
+            activities.push(activity);
+        }
 
-        for branch_name in branch_names {
-            let branch_reference = repo
-                .find_reference(&format!("refs/heads/{branch_name}"))
-                .unwrap_or_else(|e| panic!("find_reference failed: {e}"));
-            let target = branch_reference
-                .target()
-                .expect("Branch reference must have a target");
+        if self.snacking
+            && let Some(snack) = snack_activity(&repo, &mailmap)?
+        {
+            activities.push(snack);
+        }
+
+        Ok(activities)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Source;
+    use crate::filter::{IgnoreRules, RefFilter};
+    use crate::rhythm::rhythm_start;
+    use chrono::TimeDelta;
+
+    #[tokio::test]
+    async fn missing_repo_is_not_found() {
+        let source = GitSource {
+            path: "/this/path/does/not/exist".into(),
+            refs: GitRefs::default(),
+            snacking: false,
+            settings: SourceSettings::default(),
+        };
+        let activities = source
+            .get_activities(GitHosterClient::default(), rhythm_start(Utc::now()))
+            .await;
+        assert!(matches!(activities, Err(ActivityError::NotFound(_))));
+    }
 
-            // Resolve the target to get the commit
-            let commit = repo
-                .find_commit(target)
-                .unwrap_or_else(|e| panic!("find_commit failed: {e}"));
-            let secs_since_epoch = commit.time().seconds();
-            match DateTime::from_timestamp(secs_since_epoch, 0) {
-                Some(t) => branch_times.push(t),
-                None => panic!("DateTime::from_timestamp() failed"),
+    /// A repo with a commit per message on `HEAD`, made the given number of days ago.
+    fn repo_with_commits(commits: &[(&str, i64)]) -> (tempfile::TempDir, Repository) {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let now = Utc::now().timestamp();
+        {
+            let tree = repo.find_tree(tree_id).unwrap();
+            let mut parents = vec![];
+            for (message, age) in commits {
+                let signature = signature_at(now - age * 24 * 60 * 60);
+                let parent_refs: Vec<&Commit> = parents.iter().collect();
+                let oid = repo
+                    .commit(
+                        Some("HEAD"),
+                        &signature,
+                        &signature,
+                        message,
+                        &tree,
+                        &parent_refs,
+                    )
+                    .unwrap();
+                parents = vec![repo.find_commit(oid).unwrap()];
             }
         }
+        (dir, repo)
+    }
+
+    fn signature_at(secs_since_epoch: i64) -> git2::Signature<'static> {
+        let time = git2::Time::new(secs_since_epoch, 0);
+        git2::Signature::new("dawe", "dawe@example.com", &time).unwrap()
+    }
+
+    async fn activities_of(dir: &tempfile::TempDir, refs: GitRefs) -> Vec<Activity> {
+        let source = GitSource {
+            path: dir.path().to_str().unwrap().into(),
+            refs,
+            snacking: false,
+            settings: SourceSettings::default(),
+        };
+        source
+            .get_activities(GitHosterClient::default(), rhythm_start(Utc::now()))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn commits_below_the_tips_are_walked() {
+        let (dir, _repo) = repo_with_commits(&[("ancient", 365), ("older", 2), ("newest", 1)]);
+
+        let activities = activities_of(&dir, GitRefs::default()).await;
+        let messages: Vec<_> = activities
+            .iter()
+            .map(|activity| (activity.message.as_deref(), activity.git_ref.is_some()))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![(Some("newest"), true), (Some("older"), false)]
+        );
+    }
+
+    #[tokio::test]
+    async fn walk_stops_at_since() {
+        let (dir, _repo) = repo_with_commits(&[("known", 3), ("missed", 2), ("newest", 1)]);
+        let source = GitSource {
+            path: dir.path().to_str().unwrap().into(),
+            refs: GitRefs::default(),
+            snacking: false,
+            settings: SourceSettings::default(),
+        };
+
+        let since = Utc::now() - TimeDelta::days(3) + TimeDelta::hours(1);
+        let activities = source
+            .get_activities(GitHosterClient::default(), since)
+            .await
+            .unwrap();
+        let messages: Vec<_> = activities
+            .iter()
+            .map(|activity| activity.message.as_deref())
+            .collect();
+        assert_eq!(messages, vec![Some("newest"), Some("missed")]);
+    }
+
+    #[tokio::test]
+    async fn walk_goes_past_since_until_a_commit_counts() {
+        let (dir, _repo) = repo_with_commits(&[
+            ("ancient", 365),
+            ("counts", 300),
+            ("wip: bot", 2),
+            ("wip: tip", 1),
+        ]);
+        let mut settings = SourceSettings::default();
+        settings.ignore.messages = vec![regex::Regex::new("^wip").unwrap()];
+        let source = GitSource {
+            path: dir.path().to_str().unwrap().into(),
+            refs: GitRefs::default(),
+            snacking: false,
+            settings,
+        };
+
+        let activities = source
+            .get_activities(GitHosterClient::default(), rhythm_start(Utc::now()))
+            .await
+            .unwrap();
+        let messages: Vec<_> = activities
+            .iter()
+            .map(|activity| activity.message.as_deref())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![Some("wip: tip"), Some("wip: bot"), Some("counts")]
+        );
+    }
+
+    #[tokio::test]
+    async fn authors_are_mapped_by_the_mailmap() {
+        let (dir, _repo) = repo_with_commits(&[("mapped", 1)]);
+        std::fs::write(
+            dir.path().join(".mailmap"),
+            "Dawe Canonical <dawe@example.org> <dawe@example.com>\n",
+        )
+        .unwrap();
+
+        let activities = activities_of(&dir, GitRefs::default()).await;
+        assert_eq!(activities[0].actor.as_deref(), Some("Dawe Canonical"));
+        assert_eq!(activities[0].email.as_deref(), Some("dawe@example.org"));
+    }
+
+    #[tokio::test]
+    async fn empty_commits_are_ignored_if_configured() {
+        let (dir, repo) = repo_with_commits(&[("empty", 2)]);
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let blob = repo.blob(b"fn main() {}").unwrap();
+        let mut builder = repo.treebuilder(None).unwrap();
+        builder.insert("main.rs", blob, 0o100644).unwrap();
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let signature = signature_at(Utc::now().timestamp());
+        let changed = repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "changed",
+                &tree,
+                &[&head],
+            )
+            .unwrap();
+        let changed = repo.find_commit(changed).unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "empty again",
+            &tree,
+            &[&changed],
+        )
+        .unwrap();
+
+        let source = GitSource {
+            path: dir.path().to_str().unwrap().into(),
+            refs: GitRefs::default(),
+            snacking: false,
+            settings: SourceSettings {
+                ignore: IgnoreRules {
+                    empty: true,
+                    ..IgnoreRules::default()
+                },
+                ..SourceSettings::default()
+            },
+        };
+        let activities = source
+            .get_activities(GitHosterClient::default(), rhythm_start(Utc::now()))
+            .await
+            .unwrap();
+        let messages: Vec<_> = activities
+            .iter()
+            .map(|activity| activity.message.as_deref())
+            .collect();
+        assert_eq!(messages, vec![Some("changed")]);
+    }
+
+    #[tokio::test]
+    async fn merge_commits_are_merges() {
+        let (dir, repo) = repo_with_commits(&[("base", 2)]);
+        let base = repo.head().unwrap().peel_to_commit().unwrap();
+        let signature = signature_at(Utc::now().timestamp());
+        let tree = base.tree().unwrap();
+        let side = repo
+            .commit(None, &signature, &signature, "side", &tree, &[&base])
+            .unwrap();
+        let side = repo.find_commit(side).unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Merge branch 'side'",
+            &tree,
+            &[&base, &side],
+        )
+        .unwrap();
+
+        let activities = activities_of(&dir, GitRefs::default()).await;
+        let kinds: Vec<_> = activities
+            .iter()
+            .map(|activity| (activity.message.as_deref(), activity.kind.clone()))
+            .collect();
+        assert_eq!(kinds[0], (Some("Merge branch 'side'"), ActivityKind::Merge));
+        assert!(kinds.contains(&(Some("side"), ActivityKind::Commit)));
+    }
+
+    #[tokio::test]
+    async fn uncommitted_changes_are_snacks_if_configured() {
+        let (dir, _repo) = repo_with_commits(&[("committed", 2)]);
+        let source = GitSource {
+            path: dir.path().to_str().unwrap().into(),
+            refs: GitRefs::default(),
+            snacking: true,
+            settings: SourceSettings::default(),
+        };
+        let snacks = |activities: Vec<Activity>| -> Vec<Activity> {
+            activities
+                .into_iter()
+                .filter(|activity| activity.kind == ActivityKind::Snack)
+                .collect()
+        };
 
-        branch_times.into_iter().max()
+        let activities = source
+            .clone()
+            .get_activities(GitHosterClient::default(), rhythm_start(Utc::now()))
+            .await
+            .unwrap();
+        assert!(snacks(activities).is_empty());
+
+        std::fs::write(dir.path().join("wip.rs"), "fn main() {}").unwrap();
+        let activities = source
+            .get_activities(GitHosterClient::default(), rhythm_start(Utc::now()))
+            .await
+            .unwrap();
+        let snacks = snacks(activities);
+        assert_eq!(snacks.len(), 1);
+        assert!(Utc::now() - snacks[0].timestamp < chrono::TimeDelta::minutes(1));
+        assert_eq!(snacks[0].message.as_deref(), Some("1 uncommitted change"));
+
+        let activities = activities_of(&dir, GitRefs::default()).await;
+        assert!(
+            activities
+                .iter()
+                .all(|activity| activity.kind != ActivityKind::Snack)
+        );
+    }
+
+    #[tokio::test]
+    async fn excluded_branches_are_not_looked_at() {
+        let (dir, repo) = repo_with_commits(&[("human", 3)]);
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let signature = signature_at(Utc::now().timestamp());
+        let bot_branch = repo.branch("dependabot/cargo/serde", &head, false).unwrap();
+        repo.commit(
+            bot_branch.get().name(),
+            &signature,
+            &signature,
+            "bump serde",
+            &head.tree().unwrap(),
+            &[&head],
+        )
+        .unwrap();
+
+        let source = GitSource {
+            path: dir.path().to_str().unwrap().into(),
+            refs: GitRefs::default(),
+            snacking: false,
+            settings: SourceSettings {
+                branches: RefFilter {
+                    include: vec![],
+                    exclude: vec!["dependabot/*".into()],
+                },
+                ..SourceSettings::default()
+            },
+        };
+        let activities = source
+            .get_activities(GitHosterClient::default(), rhythm_start(Utc::now()))
+            .await
+            .unwrap();
+        let messages: Vec<_> = activities
+            .iter()
+            .map(|activity| activity.message.as_deref())
+            .collect();
+        assert_eq!(messages, vec![Some("human")]);
+    }
+
+    #[tokio::test]
+    async fn remote_branches_tags_and_notes_are_looked_at_if_configured() {
+        let (dir, repo) = repo_with_commits(&[("pushed", 3), ("local", 2)]);
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let pushed = head.parent(0).unwrap();
+        let now = Utc::now().timestamp();
+        repo.reference("refs/remotes/origin/main", pushed.id(), false, "fetch")
+            .unwrap();
+        repo.tag(
+            "v1.0",
+            pushed.as_object(),
+            &signature_at(now - 60),
+            "Release 1.0\n\nThe first one.",
+            false,
+        )
+        .unwrap();
+        repo.tag_lightweight("light", head.as_object(), false)
+            .unwrap();
+        repo.note(
+            &signature_at(now - 30),
+            &signature_at(now - 30),
+            None,
+            head.id(),
+            "reviewed",
+            false,
+        )
+        .unwrap();
+
+        let refs_of = |activities: Vec<Activity>| {
+            let mut refs: Vec<_> = activities
+                .into_iter()
+                .filter_map(|activity| {
+                    let git_ref = activity.git_ref?;
+                    Some((git_ref, activity.kind, activity.timestamp.timestamp()))
+                })
+                .collect();
+            refs.sort_by(|a, b| a.0.cmp(&b.0));
+            refs
+        };
+        let day = 24 * 60 * 60;
+        // the name of the default branch depends on the git config
+        let local = (
+            repo.head().unwrap().name().unwrap().to_string(),
+            ActivityKind::Commit,
+            head.time().seconds(),
+        );
+        assert_eq!(
+            refs_of(activities_of(&dir, GitRefs::default()).await),
+            vec![local.clone()]
+        );
+        assert_eq!(head.time().seconds(), now - 2 * day);
+
+        let all = GitRefs {
+            kinds: vec![
+                RefKind::Local,
+                RefKind::Remote,
+                RefKind::Tags,
+                RefKind::Notes,
+            ],
+            tag_time: TagTime::Tagger,
+        };
+        assert_eq!(
+            refs_of(activities_of(&dir, all.clone()).await),
+            vec![
+                local.clone(),
+                (
+                    "refs/notes/commits".to_string(),
+                    ActivityKind::Note,
+                    now - 30
+                ),
+                (
+                    "refs/remotes/origin/main".to_string(),
+                    ActivityKind::Commit,
+                    now - 3 * day
+                ),
+                (
+                    "refs/tags/light".to_string(),
+                    ActivityKind::Commit,
+                    now - 2 * day
+                ),
+                ("refs/tags/v1.0".to_string(), ActivityKind::Create, now - 60),
+            ]
+        );
+
+        let commit_time = GitRefs {
+            kinds: vec![RefKind::Tags],
+            tag_time: TagTime::Commit,
+        };
+        assert_eq!(
+            refs_of(activities_of(&dir, commit_time).await),
+            vec![
+                (
+                    "refs/tags/light".to_string(),
+                    ActivityKind::Commit,
+                    now - 2 * day
+                ),
+                (
+                    "refs/tags/v1.0".to_string(),
+                    ActivityKind::Commit,
+                    now - 3 * day
+                ),
+            ]
+        );
+
+        // the branches are matched by the names of the refs once more for all sources
+        for include in ["main", "origin/*", "refs/remotes/origin/*"] {
+            let source = Source::Git(GitSource {
+                path: dir.path().to_str().unwrap().into(),
+                refs: GitRefs {
+                    kinds: vec![RefKind::Remote],
+                    tag_time: TagTime::Tagger,
+                },
+                snacking: false,
+                settings: SourceSettings {
+                    branches: RefFilter {
+                        include: vec![include.into()],
+                        exclude: vec![],
+                    },
+                    ..SourceSettings::default()
+                },
+            });
+            let activities = source
+                .get_activities(GitHosterClient::default(), rhythm_start(Utc::now()))
+                .await
+                .unwrap();
+            assert_eq!(
+                refs_of(activities),
+                vec![(
+                    "refs/remotes/origin/main".to_string(),
+                    ActivityKind::Commit,
+                    now - 3 * day
+                )],
+                "{include}"
+            );
+        }
     }
 }
@@ -1,20 +1,787 @@
-use http::{HeaderMap, Method};
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use color_eyre::eyre::WrapErr;
+use http::{HeaderMap, HeaderValue, Method, StatusCode, header};
 use reqwest::Url;
 
-pub async fn get_with_headers(url: Url, header_map: HeaderMap) -> Option<String> {
-    let mut request = reqwest::Request::new(Method::GET, url);
-    request.headers_mut().extend(header_map);
-
-    match reqwest::Client::new()
-        .execute(request)
-        .await
-        .and_then(|r| r.error_for_status())
-    {
-        Ok(response) => {
-            let bytes = response.bytes().await.expect("bytes() failed");
-            let body_str = std::str::from_utf8(&bytes).expect("from_utf8() failed");
-            Some(body_str.to_string())
-        }
-        Err(_) => None,
+use crate::{activity::Activity, error::ActivityError};
+
+/// How long to pause a host that rate limits us without telling when to come back.
+const DEFAULT_BACKOFF: TimeDelta = TimeDelta::seconds(60);
+
+/// How many pages of activities to fetch per check at most.
+const MAX_PAGES: usize = 3;
+
+/// The rate limit budget of a git hoster as told by its response headers.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RateLimit {
+    /// The number of requests allowed in the current window.
+    pub limit: Option<u64>,
+    /// The number of requests left in the current window.
+    pub remaining: Option<u64>,
+    /// When the current window ends.
+    pub reset: Option<DateTime<Utc>>,
+    /// Until when we don't send any requests to the host.
+    pub paused_until: Option<DateTime<Utc>>,
+}
+
+impl RateLimit {
+    /// Reads the GitHub style `X-RateLimit-*` or the GitLab style `RateLimit-*` headers.
+    ///
+    /// Forgejo uses the GitHub style ones if rate limiting is enabled at all.
+    fn from_headers(headers: &HeaderMap, now: DateTime<Utc>) -> Self {
+        let reset =
+            header_value::<i64>(headers, &["x-ratelimit-reset", "ratelimit-reset"]).map(|reset| {
+                // an epoch timestamp or, as in the IETF draft, the seconds until the reset
+                if reset > 1_000_000_000 {
+                    DateTime::from_timestamp(reset, 0).unwrap_or(now)
+                } else {
+                    secs_after(now, reset)
+                }
+            });
+        Self {
+            limit: header_value(headers, &["x-ratelimit-limit", "ratelimit-limit"]),
+            remaining: header_value(headers, &["x-ratelimit-remaining", "ratelimit-remaining"]),
+            reset,
+            paused_until: None,
+        }
+    }
+
+    fn is_known(&self) -> bool {
+        self.limit.is_some() || self.remaining.is_some() || self.paused_until.is_some()
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.remaining == Some(0)
+    }
+
+    /// Is the host paused at the given time?
+    pub fn is_paused(&self, now: DateTime<Utc>) -> bool {
+        self.paused_until.is_some_and(|until| now < until)
+    }
+}
+
+fn header_value<T: FromStr>(headers: &HeaderMap, names: &[&str]) -> Option<T> {
+    names
+        .iter()
+        .find_map(|name| headers.get(*name))
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
+}
+
+/// The time the given seconds from a header after `now`, with negative seconds meaning `now`
+/// and ones out of range the [`DEFAULT_BACKOFF`].
+fn secs_after(now: DateTime<Utc>, secs: i64) -> DateTime<Utc> {
+    TimeDelta::try_seconds(secs.max(0))
+        .and_then(|delta| now.checked_add_signed(delta))
+        .unwrap_or(now + DEFAULT_BACKOFF)
+}
+
+/// Reads the `Retry-After` header given in seconds or as HTTP date.
+fn retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let value = headers.get(header::RETRY_AFTER)?.to_str().ok()?.trim();
+    match value.parse::<i64>() {
+        Ok(secs) => Some(secs_after(now, secs)),
+        Err(_) => DateTime::parse_from_rfc2822(value)
+            .ok()
+            .map(|date| date.to_utc()),
+    }
+}
+
+/// Reads the url of the next page from the `Link` header.
+fn next_link(headers: &HeaderMap) -> Option<Url> {
+    let value = headers.get(header::LINK)?.to_str().ok()?;
+    value.split(',').find_map(|link| {
+        let (target, params) = link.split_once(';')?;
+        let is_next = params
+            .split(';')
+            .any(|param| matches!(param.trim(), "rel=\"next\"" | "rel=next"));
+        let target = target.trim().strip_prefix('<')?.strip_suffix('>')?;
+        is_next.then(|| Url::parse(target).ok()).flatten()
+    })
+}
+
+/// How to talk to the git hosters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpSettings {
+    /// How long to wait for a connection to be established.
+    pub connect_timeout: Duration,
+    /// How long to wait for the next bit of a response.
+    pub read_timeout: Duration,
+    /// How often to retry a request after a network or server error.
+    pub retries: u32,
+    /// The delay before the first retry, doubled for every further one.
+    pub retry_delay: Duration,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+            retries: 2,
+            retry_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+impl HttpSettings {
+    /// The exponential backoff before the given retry, with up to 50% of random jitter.
+    fn backoff(&self, retry: u32) -> Duration {
+        let delay = self.retry_delay.saturating_mul(2u32.saturating_pow(retry));
+        delay + delay.mul_f64(fastrand::f64() * 0.5)
+    }
+}
+
+/// How to connect to a git hoster, given globally or per source.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ConnectionSettings {
+    /// The proxy to send all requests through.
+    pub proxy: Option<String>,
+    /// The hosts to reach without the proxy.
+    pub no_proxy: Vec<String>,
+    /// PEM files with additionally trusted root certificates.
+    pub root_certificates: Vec<PathBuf>,
+    /// The PEM file with the client certificate for mutual TLS.
+    pub client_certificate: Option<PathBuf>,
+    /// The PEM file with the PKCS #8 key of the client certificate.
+    pub client_key: Option<PathBuf>,
+    /// Skip the certificate verification for loopback hosts like `localhost`.
+    pub insecure_localhost: bool,
+}
+
+impl ConnectionSettings {
+    /// The settings to use for the given url, only skipping the verification for loopback hosts.
+    fn for_url(&self, url: &Url) -> Self {
+        let host = url.host_str().unwrap_or_default();
+        let is_loopback = host == "localhost"
+            || host.ends_with(".localhost")
+            || host
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback());
+        Self {
+            insecure_localhost: self.insecure_localhost && is_loopback,
+            ..self.clone()
+        }
+    }
+}
+
+/// Builds a pooled http client, reusing connections across checks.
+fn build_http_client(
+    settings: &HttpSettings,
+    connection: &ConnectionSettings,
+) -> color_eyre::Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(settings.connect_timeout)
+        .read_timeout(settings.read_timeout);
+
+    if let Some(proxy) = &connection.proxy {
+        let no_proxy = reqwest::NoProxy::from_string(&connection.no_proxy.join(","));
+        let proxy = reqwest::Proxy::all(proxy.as_str())
+            .wrap_err_with(|| format!("invalid proxy {proxy}"))?
+            .no_proxy(no_proxy);
+        builder = builder.proxy(proxy);
+    }
+
+    for path in &connection.root_certificates {
+        let pem = std::fs::read(path)
+            .wrap_err_with(|| format!("failed to read root certificate {}", path.display()))?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+            .wrap_err_with(|| format!("invalid root certificate {}", path.display()))?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    match (&connection.client_certificate, &connection.client_key) {
+        (Some(certificate_path), Some(key_path)) => {
+            let certificate = std::fs::read(certificate_path).wrap_err_with(|| {
+                format!(
+                    "failed to read client certificate {}",
+                    certificate_path.display()
+                )
+            })?;
+            let key = std::fs::read(key_path)
+                .wrap_err_with(|| format!("failed to read client key {}", key_path.display()))?;
+            let identity = reqwest::Identity::from_pkcs8_pem(&certificate, &key)
+                .wrap_err("invalid client certificate or key")?;
+            builder = builder.identity(identity);
+        }
+        (None, None) => {}
+        _ => color_eyre::eyre::bail!("client certificate and client key must be given together"),
+    }
+
+    if connection.insecure_localhost {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    builder.build().wrap_err("failed to build the http client")
+}
+
+/// What a response is cached by: the url and the auth headers it was fetched with, so a
+/// response isn't served to a request with another token.
+type CacheKey = (Url, Option<HeaderValue>, Option<HeaderValue>);
+
+fn cache_key(url: &Url, header_map: &HeaderMap) -> CacheKey {
+    (
+        url.clone(),
+        header_map.get(header::AUTHORIZATION).cloned(),
+        header_map.get("PRIVATE-TOKEN").cloned(),
+    )
+}
+
+/// A successful response, kept to make conditional requests for the same url.
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    etag: Option<HeaderValue>,
+    last_modified: Option<HeaderValue>,
+    page: Page,
+}
+
+/// The body of a response and the url of the page after it, if any.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    pub body: String,
+    pub next: Option<Url>,
+}
+
+/// The client for requests to git hosters, shared by all sources.
+///
+/// It keeps track of the rate limit of each host and pauses the requests to a host until its
+/// limit resets. The responses of first pages are cached by url and auth and revalidated with
+/// `If-None-Match` and `If-Modified-Since`, as unchanged `304` answers don't count against
+/// GitHub's rate limit. Later pages are behind ever changing cursors, so they aren't cached.
+#[derive(Debug, Clone, Default)]
+pub struct GitHosterClient {
+    /// The pooled http clients for the different connection settings.
+    clients: Arc<Mutex<HashMap<ConnectionSettings, reqwest::Client>>>,
+    /// How to talk to the git hosters.
+    settings: HttpSettings,
+    /// The last known rate limit of each host.
+    rate_limits: Arc<Mutex<HashMap<String, RateLimit>>>,
+    /// The last successful response of each first page.
+    responses: Arc<Mutex<HashMap<CacheKey, CachedResponse>>>,
+}
+
+impl GitHosterClient {
+    /// Constructs a new [`GitHosterClient`] with the given settings.
+    pub fn new(settings: HttpSettings) -> Self {
+        Self {
+            settings,
+            ..Self::default()
+        }
+    }
+
+    /// Builds the http client for the given connection settings ahead of the first request,
+    /// failing early on unreadable certificates or invalid proxies.
+    pub fn add_connection(&self, connection: &ConnectionSettings) -> color_eyre::Result<()> {
+        self.http_client(connection).map(|_| ())
+    }
+
+    fn http_client(&self, connection: &ConnectionSettings) -> color_eyre::Result<reqwest::Client> {
+        let mut clients = self
+            .clients
+            .lock()
+            .map_err(|_| color_eyre::eyre::eyre!("http clients are poisoned"))?;
+        match clients.get(connection) {
+            Some(client) => Ok(client.clone()),
+            None => {
+                let client = build_http_client(&self.settings, connection)?;
+                clients.insert(connection.clone(), client.clone());
+                Ok(client)
+            }
+        }
+    }
+
+    /// Executes the request, retrying with backoff on network and server errors.
+    async fn execute(
+        &self,
+        request: reqwest::Request,
+        connection: &ConnectionSettings,
+    ) -> Result<reqwest::Response, ActivityError> {
+        let http = self
+            .http_client(&connection.for_url(request.url()))
+            .map_err(|e| ActivityError::Internal(format!("{e:#}")))?;
+        let mut retry = 0;
+        loop {
+            let attempt = request
+                .try_clone()
+                .ok_or_else(|| ActivityError::Internal("request can't be retried".into()))?;
+            let can_retry = retry < self.settings.retries;
+            match http.execute(attempt).await {
+                Ok(response) if can_retry && response.status().is_server_error() => {}
+                Ok(response) => return Ok(response),
+                Err(e) if can_retry && (e.is_connect() || e.is_timeout()) => {}
+                Err(e) => return Err(ActivityError::Network(e.to_string())),
+            }
+            tokio::time::sleep(self.settings.backoff(retry)).await;
+            retry += 1;
+        }
+    }
+
+    /// The last known rate limit of the given host.
+    pub fn rate_limit(&self, host: &str) -> Option<RateLimit> {
+        self.rate_limits
+            .lock()
+            .ok()
+            .and_then(|rate_limits| rate_limits.get(host).copied())
+    }
+
+    fn update_rate_limit(&self, host: String, rate_limit: RateLimit) {
+        if let Ok(mut rate_limits) = self.rate_limits.lock() {
+            rate_limits.insert(host, rate_limit);
+        }
+    }
+
+    fn cached_response(&self, key: &CacheKey) -> Option<CachedResponse> {
+        self.responses
+            .lock()
+            .ok()
+            .and_then(|responses| responses.get(key).cloned())
+    }
+
+    fn cache_response(&self, key: CacheKey, response: CachedResponse) {
+        if let Ok(mut responses) = self.responses.lock() {
+            responses.insert(key, response);
+        }
+    }
+
+    /// Fetches a first page, revalidating its cached response.
+    pub async fn get_with_headers(
+        &self,
+        url: Url,
+        header_map: HeaderMap,
+        connection: &ConnectionSettings,
+    ) -> Result<Page, ActivityError> {
+        self.get_page(url, header_map, connection, true).await
+    }
+
+    /// Fetches a page, caching and revalidating its response if `cached` is set.
+    async fn get_page(
+        &self,
+        url: Url,
+        header_map: HeaderMap,
+        connection: &ConnectionSettings,
+        cached: bool,
+    ) -> Result<Page, ActivityError> {
+        let host = url.host_str().unwrap_or_default().to_string();
+        if self
+            .rate_limit(&host)
+            .is_some_and(|rate_limit| rate_limit.is_paused(Utc::now()))
+        {
+            return Err(ActivityError::RateLimited);
+        }
+
+        let key = cached.then(|| cache_key(&url, &header_map));
+        let mut request = reqwest::Request::new(Method::GET, url.clone());
+        request.headers_mut().extend(header_map);
+        let cached = key.as_ref().and_then(|key| self.cached_response(key));
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                request
+                    .headers_mut()
+                    .insert(header::IF_NONE_MATCH, etag.clone());
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request
+                    .headers_mut()
+                    .insert(header::IF_MODIFIED_SINCE, last_modified.clone());
+            }
+        }
+
+        let response = self.execute(request, connection).await?;
+
+        let status = response.status();
+        let now = Utc::now();
+        let mut rate_limit = RateLimit::from_headers(response.headers(), now);
+        let retry_after = retry_after(response.headers(), now);
+        // GitHub answers with 403 instead of 429 when the rate limit is used up, and its
+        // secondary rate limit with 403 and Retry-After while budget remains.
+        let rate_limited = status == StatusCode::TOO_MANY_REQUESTS
+            || (status == StatusCode::FORBIDDEN
+                && (retry_after.is_some() || rate_limit.is_exhausted()));
+        if rate_limited || rate_limit.is_exhausted() {
+            let until = retry_after
+                .or(rate_limit.reset)
+                .unwrap_or(now + DEFAULT_BACKOFF);
+            rate_limit.paused_until = Some(until);
+        }
+        if rate_limit.is_known() {
+            self.update_rate_limit(host, rate_limit);
+        }
+
+        match status {
+            _ if rate_limited => Err(ActivityError::RateLimited),
+            StatusCode::NOT_MODIFIED => match cached {
+                Some(cached) => Ok(cached.page),
+                None => Err(ActivityError::Network("unexpected status 304".into())),
+            },
+            status if status.is_success() => {
+                let etag = response.headers().get(header::ETAG).cloned();
+                let last_modified = response.headers().get(header::LAST_MODIFIED).cloned();
+                let next = next_link(response.headers());
+                let bytes = response
+                    .bytes()
+                    .await
+                    .map_err(|e| ActivityError::Network(e.to_string()))?;
+                let body = String::from_utf8(bytes.to_vec())
+                    .map_err(|e| ActivityError::Parse(e.to_string()))?;
+                let page = Page { body, next };
+                if let Some(key) = key
+                    && (etag.is_some() || last_modified.is_some())
+                {
+                    let response = CachedResponse {
+                        etag,
+                        last_modified,
+                        page: page.clone(),
+                    };
+                    self.cache_response(key, response);
+                }
+                Ok(page)
+            }
+            StatusCode::NOT_FOUND => Err(ActivityError::NotFound(url.to_string())),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Err(ActivityError::Unauthorized),
+            status => Err(ActivityError::Network(format!(
+                "unexpected status {status}"
+            ))),
+        }
+    }
+
+    /// Fetches the activities page by page, following the `Link` headers until an activity
+    /// not newer than `since` shows up or [`MAX_PAGES`] pages are fetched.
+    pub async fn get_activities_since(
+        &self,
+        url: Url,
+        header_map: HeaderMap,
+        connection: &ConnectionSettings,
+        since: DateTime<Utc>,
+        parse: impl Fn(&str) -> Result<Vec<Activity>, ActivityError>,
+    ) -> Result<Vec<Activity>, ActivityError> {
+        let mut activities = vec![];
+        let mut next = Some(url);
+        for page_index in 0..MAX_PAGES {
+            let Some(url) = next.take() else {
+                break;
+            };
+            let page = self
+                .get_page(url, header_map.clone(), connection, page_index == 0)
+                .await?;
+            let page_activities = parse(&page.body)?;
+            let reached_since = page_activities
+                .iter()
+                .any(|activity| activity.timestamp <= since);
+            activities.extend(page_activities);
+            if reached_since {
+                break;
+            }
+            next = page.next;
+        }
+        Ok(activities)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        task::JoinHandle,
+    };
+
+    /// Answers each connection with the next of the given raw responses and returns the requests.
+    async fn serve(responses: Vec<&'static str>) -> (Url, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let server = tokio::spawn(async move {
+            let mut requests = vec![];
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0; 4096];
+                let n = stream.read(&mut buf).await.unwrap();
+                requests.push(String::from_utf8_lossy(&buf[..n]).to_lowercase());
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+        (url, server)
+    }
+
+    #[tokio::test]
+    async fn not_modified_reuses_cached_body() {
+        let (url, server) = serve(vec![
+            "HTTP/1.1 200 OK\r\nETag: \"abc\"\r\nContent-Length: 4\r\nConnection: close\r\n\r\nbody",
+            "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n",
+        ])
+        .await;
+        let client = GitHosterClient::default();
+
+        let first = client
+            .get_with_headers(
+                url.clone(),
+                HeaderMap::new(),
+                &ConnectionSettings::default(),
+            )
+            .await;
+        let second = client
+            .get_with_headers(url, HeaderMap::new(), &ConnectionSettings::default())
+            .await;
+        let requests = server.await.unwrap();
+
+        assert_eq!(first.map(|page| page.body), Ok("body".into()));
+        assert_eq!(second.map(|page| page.body), Ok("body".into()));
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"abc\""));
+    }
+
+    #[tokio::test]
+    async fn cached_responses_are_kept_apart_by_auth() {
+        let (url, server) = serve(vec![
+            "HTTP/1.1 200 OK\r\nETag: \"abc\"\r\nContent-Length: 4\r\nConnection: close\r\n\r\nbody",
+            "HTTP/1.1 200 OK\r\nETag: \"xyz\"\r\nContent-Length: 4\r\nConnection: close\r\n\r\nbody",
+        ])
+        .await;
+        let client = GitHosterClient::default();
+
+        for token in ["token a", "token b"] {
+            client
+                .get_with_headers(
+                    url.clone(),
+                    headers(&[("authorization", token)]),
+                    &ConnectionSettings::default(),
+                )
+                .await
+                .unwrap();
+        }
+        let requests = server.await.unwrap();
+
+        assert!(!requests[1].contains("if-none-match"));
+        assert_eq!(client.responses.lock().unwrap().len(), 2);
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retried() {
+        let (url, server) = serve(vec![
+            "HTTP/1.1 502 Bad Gateway\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nContent-Length: 4\r\nConnection: close\r\n\r\nbody",
+        ])
+        .await;
+        let settings = HttpSettings {
+            retries: 1,
+            retry_delay: Duration::from_millis(1),
+            ..HttpSettings::default()
+        };
+        let client = GitHosterClient::new(settings);
+
+        let body = client
+            .get_with_headers(url, HeaderMap::new(), &ConnectionSettings::default())
+            .await;
+        let requests = server.await.unwrap();
+
+        assert_eq!(body.map(|page| page.body), Ok("body".into()));
+        assert_eq!(requests.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn forbidden_with_retry_after_pauses_the_host() {
+        let (url, server) = serve(vec![
+            "HTTP/1.1 403 Forbidden\r\nRetry-After: 60\r\nX-RateLimit-Remaining: 42\r\n\
+             Content-Length: 0\r\nConnection: close\r\n\r\n",
+        ])
+        .await;
+        let client = GitHosterClient::default();
+        let host = url.host_str().unwrap().to_string();
+
+        let first = client
+            .get_with_headers(
+                url.clone(),
+                HeaderMap::new(),
+                &ConnectionSettings::default(),
+            )
+            .await;
+        let second = client
+            .get_with_headers(url, HeaderMap::new(), &ConnectionSettings::default())
+            .await;
+        server.await.unwrap();
+
+        assert_eq!(first.map(|page| page.body), Err(ActivityError::RateLimited));
+        assert_eq!(
+            second.map(|page| page.body),
+            Err(ActivityError::RateLimited)
+        );
+        assert!(client.rate_limit(&host).unwrap().is_paused(Utc::now()));
+    }
+
+    #[test]
+    fn insecure_only_for_loopback_hosts() {
+        let connection = ConnectionSettings {
+            insecure_localhost: true,
+            ..ConnectionSettings::default()
+        };
+        for url in [
+            "https://localhost:3000",
+            "https://127.0.0.1",
+            "https://[::1]",
+        ] {
+            let url = Url::parse(url).unwrap();
+            assert!(connection.for_url(&url).insecure_localhost);
+        }
+        let url = Url::parse("https://codeberg.org").unwrap();
+        assert!(!connection.for_url(&url).insecure_localhost);
+    }
+
+    #[test]
+    fn unreadable_certificates_fail_early() {
+        let client = GitHosterClient::default();
+        let connection = ConnectionSettings {
+            root_certificates: vec!["/this/path/does/not/exist.pem".into()],
+            ..ConnectionSettings::default()
+        };
+        assert!(client.add_connection(&connection).is_err());
+
+        let connection = ConnectionSettings {
+            client_certificate: Some("/this/path/does/not/exist.pem".into()),
+            ..ConnectionSettings::default()
+        };
+        assert!(client.add_connection(&connection).is_err());
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_with_jitter() {
+        let settings = HttpSettings {
+            retry_delay: Duration::from_millis(100),
+            ..HttpSettings::default()
+        };
+        for (retry, base) in [(0, 100), (1, 200), (2, 400)] {
+            let backoff = settings.backoff(retry);
+            assert!(backoff >= Duration::from_millis(base));
+            assert!(backoff <= Duration::from_millis(base * 3 / 2));
+        }
+    }
+
+    #[test]
+    fn github_rate_limit_headers() {
+        let now = Utc::now();
+        let headers = headers(&[
+            ("x-ratelimit-limit", "60"),
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", "1760000000"),
+        ]);
+        let rate_limit = RateLimit::from_headers(&headers, now);
+
+        assert_eq!(rate_limit.limit, Some(60));
+        assert_eq!(rate_limit.remaining, Some(0));
+        assert_eq!(rate_limit.reset, DateTime::from_timestamp(1760000000, 0));
+        assert!(rate_limit.is_exhausted());
+    }
+
+    #[test]
+    fn gitlab_rate_limit_headers() {
+        let now = Utc::now();
+        let headers = headers(&[
+            ("ratelimit-limit", "2000"),
+            ("ratelimit-remaining", "1999"),
+            ("ratelimit-reset", "30"),
+        ]);
+        let rate_limit = RateLimit::from_headers(&headers, now);
+
+        assert_eq!(rate_limit.limit, Some(2000));
+        assert_eq!(rate_limit.remaining, Some(1999));
+        assert_eq!(rate_limit.reset, Some(now + TimeDelta::seconds(30)));
+        assert!(!rate_limit.is_exhausted());
+    }
+
+    #[test]
+    fn absurd_header_values_are_clamped() {
+        let now = Utc::now();
+        let huge = headers(&[("retry-after", "9223372036854775807")]);
+        assert_eq!(retry_after(&huge, now), Some(now + DEFAULT_BACKOFF));
+        let negative = headers(&[("retry-after", "-5")]);
+        assert_eq!(retry_after(&negative, now), Some(now));
+
+        let headers = headers(&[("ratelimit-remaining", "0"), ("ratelimit-reset", "-30")]);
+        assert_eq!(RateLimit::from_headers(&headers, now).reset, Some(now));
+    }
+
+    #[test]
+    fn next_link_is_read_from_link_header() {
+        let github = headers(&[(
+            "link",
+            "<https://api.github.com/repositories/1/activity?after=abc>; rel=\"next\", \
+             <https://api.github.com/repositories/1/activity?before=xyz>; rel=\"prev\"",
+        )]);
+        assert_eq!(
+            next_link(&github),
+            Url::parse("https://api.github.com/repositories/1/activity?after=abc").ok()
+        );
+        let last_page = headers(&[(
+            "link",
+            "<https://gitlab.com/api/v4/projects/1/events?page=1>; rel=\"first\"",
+        )]);
+        assert_eq!(next_link(&last_page), None);
+        assert_eq!(next_link(&HeaderMap::new()), None);
+    }
+
+    #[tokio::test]
+    async fn paging_stops_at_old_activities() {
+        let (url, server) = serve(vec![
+            "HTTP/1.1 200 OK\r\nLink: <http://127.0.0.1:1/?page=2>; rel=\"next\"\r\n\
+             Content-Length: 2\r\nConnection: close\r\n\r\n[]",
+        ])
+        .await;
+        let client = GitHosterClient::default();
+        let old = Activity {
+            timestamp: Utc::now() - TimeDelta::weeks(52),
+            kind: crate::activity::ActivityKind::Push,
+            actor: None,
+            email: None,
+            git_ref: None,
+            message: None,
+        };
+
+        let activities = client
+            .get_activities_since(
+                url,
+                HeaderMap::new(),
+                &ConnectionSettings::default(),
+                Utc::now() - TimeDelta::weeks(26),
+                |_| Ok(vec![old.clone()]),
+            )
+            .await;
+        let requests = server.await.unwrap();
+
+        assert_eq!(activities, Ok(vec![old.clone()]));
+        assert_eq!(requests.len(), 1);
+    }
+
+    #[test]
+    fn retry_after_in_secs_and_as_date() {
+        let now = Utc::now();
+        let secs = headers(&[("retry-after", "120")]);
+        assert_eq!(retry_after(&secs, now), Some(now + TimeDelta::seconds(120)));
+
+        let date = headers(&[("retry-after", "Wed, 21 Oct 2015 07:28:00 GMT")]);
+        assert_eq!(
+            retry_after(&date, now),
+            DateTime::from_timestamp(1445412480, 0)
+        );
     }
 }
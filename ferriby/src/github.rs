@@ -1,28 +1,32 @@
-use std::cell::LazyCell;
-
-use chrono::NaiveDateTime;
 use chrono::{DateTime, offset::Utc};
 use http::{HeaderMap, header};
-use regex::Regex;
 use reqwest::Url;
+use serde::Deserialize;
 
-use crate::app::ActivitySource;
-use crate::githoster::get_with_headers;
+use crate::activity::{Activity, ActivityKind};
+use crate::app::{ActivitySource, SourceSettings};
+use crate::error::ActivityError;
+use crate::githoster::GitHosterClient;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct GitHubSource {
     pub owner: String,
     pub repo: String,
     pub pat: Option<String>,
+    pub settings: SourceSettings,
 }
 
 impl ActivitySource for GitHubSource {
-    async fn get_last_activity(self) -> Option<DateTime<Utc>> {
+    async fn get_activities(
+        self,
+        client: GitHosterClient,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Activity>, ActivityError> {
         let url = format!(
-            "https://api.github.com/repos/{}/{}/activity",
+            "https://api.github.com/repos/{}/{}/activity?per_page=100",
             self.owner, self.repo
         );
-        let url = Url::parse(url.as_str()).expect("Url creation failed");
+        let url = Url::parse(url.as_str()).map_err(|e| ActivityError::Parse(e.to_string()))?;
 
         let mut headers: HeaderMap = HeaderMap::new();
         headers.insert(
@@ -39,36 +43,62 @@ impl ActivitySource for GitHubSource {
         );
         if let Some(token) = &self.pat {
             let pat = header::HeaderValue::from_str(format!("Bearer {token}").as_str())
-                .expect("bad github pat");
+                .map_err(|_| ActivityError::Unauthorized)?;
             headers.insert(header::AUTHORIZATION, pat);
         }
 
-        match get_with_headers(url, headers).await {
-            Some(body) => {
-                let timestamps = GitHubSource::parse_timestamps(body.as_str());
-                timestamps.into_iter().max()
-            }
-            None => None,
+        client
+            .get_activities_since(
+                url,
+                headers,
+                &self.settings.connection,
+                since,
+                GitHubSource::parse_activities,
+            )
+            .await
+    }
+}
+
+/// An entry of the `/repos/{owner}/{repo}/activity` response.
+#[derive(Debug, Deserialize)]
+struct GitHubActivity {
+    timestamp: DateTime<Utc>,
+    activity_type: String,
+    #[serde(rename = "ref")]
+    git_ref: Option<String>,
+    actor: Option<GitHubActor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubActor {
+    login: String,
+}
+
+impl From<GitHubActivity> for Activity {
+    fn from(activity: GitHubActivity) -> Self {
+        let kind = match activity.activity_type.as_str() {
+            "push" | "force_push" => ActivityKind::Push,
+            "branch_creation" => ActivityKind::Create,
+            "branch_deletion" => ActivityKind::Delete,
+            "pr_merge" | "merge_queue_merge" => ActivityKind::Merge,
+            other => ActivityKind::Other(other.to_string()),
+        };
+        Activity {
+            timestamp: activity.timestamp,
+            kind,
+            actor: activity.actor.map(|actor| actor.login),
+            email: None,
+            git_ref: activity.git_ref,
+            message: None,
         }
     }
 }
 
 impl GitHubSource {
-    fn parse_timestamps(response: &str) -> Vec<DateTime<Utc>> {
-        let re: LazyCell<Regex> = LazyCell::new(|| {
-            Regex::new("\"timestamp\":\"(\\d\\d\\d\\d-\\d\\d-\\d\\dT\\d\\d:\\d\\d:\\d\\dZ)\"")
-                .unwrap()
-        });
-
-        re.captures_iter(response)
-            .map(|m| {
-                let s = m.get(1).unwrap().as_str();
-                let dt = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%SZ")
-                    .expect("unexpected timestamp format");
-                let secs = dt.and_utc().timestamp();
-                DateTime::from_timestamp(secs, 0).expect("from_timestamp failed")
-            })
-            .collect()
+    fn parse_activities(response: &str) -> Result<Vec<Activity>, ActivityError> {
+        let activities: Vec<GitHubActivity> =
+            serde_json::from_str(response).map_err(|e| ActivityError::Parse(e.to_string()))?;
+        Ok(activities.into_iter().map(Activity::from).collect())
     }
 }
 
@@ -80,24 +110,49 @@ mod tests {
 
     #[test]
     fn github_parse() {
-        let s = "\"timestamp\":\"2025-05-16T20:41:19Z\" bla foo\
-            \"timestamp\":\"2025-10-18T03:01:09Z\"";
-        let parsed = GitHubSource::parse_timestamps(s);
+        let s = r#"[
+            {
+                "id": 1,
+                "ref": "refs/heads/main",
+                "timestamp": "2025-05-16T20:41:19Z",
+                "activity_type": "push",
+                "actor": { "login": "dawedawe", "id": 42 }
+            },
+            {
+                "id": 2,
+                "ref": "refs/heads/feature",
+                "timestamp" : "2025-10-18T03:01:09Z",
+                "activity_type": "pr_merge",
+                "actor": null
+            }
+        ]"#;
+        let parsed = GitHubSource::parse_activities(s).unwrap();
 
         assert_eq!(parsed.len(), 2);
 
-        assert_eq!(parsed[0].year(), 2025);
-        assert_eq!(parsed[0].month(), 5);
-        assert_eq!(parsed[0].day(), 16);
-        assert_eq!(parsed[0].hour(), 20);
-        assert_eq!(parsed[0].minute(), 41);
-        assert_eq!(parsed[0].second(), 19);
-
-        assert_eq!(parsed[1].year(), 2025);
-        assert_eq!(parsed[1].month(), 10);
-        assert_eq!(parsed[1].day(), 18);
-        assert_eq!(parsed[1].hour(), 3);
-        assert_eq!(parsed[1].minute(), 1);
-        assert_eq!(parsed[1].second(), 9);
+        assert_eq!(parsed[0].timestamp.year(), 2025);
+        assert_eq!(parsed[0].timestamp.month(), 5);
+        assert_eq!(parsed[0].timestamp.day(), 16);
+        assert_eq!(parsed[0].timestamp.hour(), 20);
+        assert_eq!(parsed[0].timestamp.minute(), 41);
+        assert_eq!(parsed[0].timestamp.second(), 19);
+        assert_eq!(parsed[0].kind, ActivityKind::Push);
+        assert_eq!(parsed[0].actor.as_deref(), Some("dawedawe"));
+        assert_eq!(parsed[0].git_ref.as_deref(), Some("refs/heads/main"));
+
+        assert_eq!(parsed[1].timestamp.year(), 2025);
+        assert_eq!(parsed[1].timestamp.month(), 10);
+        assert_eq!(parsed[1].timestamp.day(), 18);
+        assert_eq!(parsed[1].timestamp.hour(), 3);
+        assert_eq!(parsed[1].timestamp.minute(), 1);
+        assert_eq!(parsed[1].timestamp.second(), 9);
+        assert_eq!(parsed[1].kind, ActivityKind::Merge);
+        assert_eq!(parsed[1].actor, None);
+    }
+
+    #[test]
+    fn github_parse_invalid_response_errs() {
+        let parsed = GitHubSource::parse_activities("{ \"message\": \"Not Found\" }");
+        assert!(matches!(parsed, Err(ActivityError::Parse(_))));
     }
 }
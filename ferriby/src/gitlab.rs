@@ -1,13 +1,12 @@
-use std::cell::LazyCell;
-
-use chrono::NaiveDateTime;
 use chrono::{DateTime, offset::Utc};
 use http::{HeaderMap, header};
-use regex::Regex;
 use reqwest::Url;
+use serde::Deserialize;
 
-use crate::app::ActivitySource;
-use crate::githoster::get_with_headers;
+use crate::activity::{Activity, ActivityKind};
+use crate::app::{ActivitySource, SourceSettings};
+use crate::error::ActivityError;
+use crate::githoster::GitHosterClient;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct GitLabSource {
@@ -15,15 +14,20 @@ pub struct GitLabSource {
     pub project_id: String,
     pub project_name: String,
     pub pat: Option<String>,
+    pub settings: SourceSettings,
 }
 
 impl ActivitySource for GitLabSource {
-    async fn get_last_activity(self) -> Option<DateTime<Utc>> {
+    async fn get_activities(
+        self,
+        client: GitHosterClient,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Activity>, ActivityError> {
         let url = format!(
-            "https://{}/api/v4/projects/{}/events",
+            "https://{}/api/v4/projects/{}/events?per_page=100",
             self.hostname, self.project_id
         );
-        let url = Url::parse(url.as_str()).expect("Url creation failed");
+        let url = Url::parse(url.as_str()).map_err(|e| ActivityError::Parse(e.to_string()))?;
 
         let mut headers: HeaderMap = HeaderMap::new();
         headers.insert(
@@ -35,38 +39,89 @@ impl ActivitySource for GitLabSource {
             header::HeaderValue::from_static("application/json"),
         );
         if let Some(token) = &self.pat {
-            let pat = header::HeaderValue::from_str(token.as_str()).expect("bad gitlab pat");
+            let pat = header::HeaderValue::from_str(token.as_str())
+                .map_err(|_| ActivityError::Unauthorized)?;
             headers.insert("PRIVATE-TOKEN", pat);
         }
 
-        match get_with_headers(url, headers).await {
-            Some(body) => {
-                let timestamps = GitLabSource::parse_timestamps(body.as_str());
-                timestamps.into_iter().max()
-            }
-            None => None,
+        client
+            .get_activities_since(
+                url,
+                headers,
+                &self.settings.connection,
+                since,
+                GitLabSource::parse_activities,
+            )
+            .await
+    }
+}
+
+/// An entry of the `/projects/{id}/events` response.
+#[derive(Debug, Deserialize)]
+struct GitLabEvent {
+    created_at: DateTime<Utc>,
+    action_name: String,
+    author_username: Option<String>,
+    target_title: Option<String>,
+    push_data: Option<GitLabPushData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabPushData {
+    action: String,
+    ref_type: Option<String>,
+    #[serde(rename = "ref")]
+    git_ref: Option<String>,
+    commit_title: Option<String>,
+}
+
+/// The full name of a ref GitLab only gives the short name and the type of, like
+/// `refs/tags/v1.0` for the tag `v1.0`, so it's matched like the refs of other sources.
+fn full_ref(ref_type: Option<String>, git_ref: Option<String>) -> Option<String> {
+    let git_ref = git_ref?;
+    Some(match ref_type.as_deref() {
+        Some("branch") => format!("refs/heads/{git_ref}"),
+        Some("tag") => format!("refs/tags/{git_ref}"),
+        _ => git_ref,
+    })
+}
+
+impl From<GitLabEvent> for Activity {
+    fn from(event: GitLabEvent) -> Self {
+        let action = match &event.push_data {
+            Some(push_data) => push_data.action.as_str(),
+            None => event.action_name.as_str(),
+        };
+        let kind = match action {
+            "pushed" => ActivityKind::Push,
+            "created" => ActivityKind::Create,
+            "removed" | "deleted" => ActivityKind::Delete,
+            "accepted" => ActivityKind::Merge,
+            _ => ActivityKind::Other(event.action_name.clone()),
+        };
+        let (git_ref, message) = match event.push_data {
+            Some(push_data) => (
+                full_ref(push_data.ref_type, push_data.git_ref),
+                push_data.commit_title,
+            ),
+            None => (None, event.target_title),
+        };
+        Activity {
+            timestamp: event.created_at,
+            kind,
+            actor: event.author_username,
+            email: None,
+            git_ref,
+            message,
         }
     }
 }
 
 impl GitLabSource {
-    fn parse_timestamps(response: &str) -> Vec<DateTime<Utc>> {
-        let re: LazyCell<Regex> = LazyCell::new(|| {
-            Regex::new(
-                "\"created_at\":\"(\\d\\d\\d\\d-\\d\\d-\\d\\dT\\d\\d:\\d\\d:\\d\\d.\\d\\d\\dZ)\"",
-            )
-            .unwrap()
-        });
-
-        re.captures_iter(response)
-            .map(|m| {
-                let s = m.get(1).unwrap().as_str();
-                let dt = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.fZ")
-                    .expect("unexpected timestamp format");
-                let secs = dt.and_utc().timestamp();
-                DateTime::from_timestamp(secs, 0).expect("from_timestamp failed")
-            })
-            .collect()
+    fn parse_activities(response: &str) -> Result<Vec<Activity>, ActivityError> {
+        let events: Vec<GitLabEvent> =
+            serde_json::from_str(response).map_err(|e| ActivityError::Parse(e.to_string()))?;
+        Ok(events.into_iter().map(Activity::from).collect())
     }
 }
 
@@ -77,25 +132,51 @@ mod tests {
     use super::*;
 
     #[test]
-    fn github_parse() {
-        let s = "\"created_at\":\"2025-07-14T21:12:15.564Z\" bla foo\
-            \"created_at\":\"2025-07-14T21:12:15.137Z\"";
-        let parsed = GitLabSource::parse_timestamps(s);
+    fn gitlab_parse() {
+        let s = r#"[
+            {
+                "id": 1,
+                "action_name": "pushed to",
+                "author_username": "dawe",
+                "created_at": "2025-07-14T21:12:15.564Z",
+                "target_title": null,
+                "push_data": {
+                    "commit_count": 1,
+                    "action": "pushed",
+                    "ref_type": "branch",
+                    "ref": "main",
+                    "commit_title": "Fix \"created_at\":\"2000-01-01T00:00:00.000Z\" handling"
+                }
+            },
+            {
+                "id": 2,
+                "action_name": "accepted",
+                "author_username": "dawe",
+                "created_at": "2025-07-14T21:12:15.137Z",
+                "target_title": "Add gitlab support"
+            }
+        ]"#;
+        let parsed = GitLabSource::parse_activities(s).unwrap();
 
         assert_eq!(parsed.len(), 2);
 
-        assert_eq!(parsed[0].year(), 2025);
-        assert_eq!(parsed[0].month(), 7);
-        assert_eq!(parsed[0].day(), 14);
-        assert_eq!(parsed[0].hour(), 21);
-        assert_eq!(parsed[0].minute(), 12);
-        assert_eq!(parsed[0].second(), 15);
-
-        assert_eq!(parsed[1].year(), 2025);
-        assert_eq!(parsed[1].month(), 7);
-        assert_eq!(parsed[1].day(), 14);
-        assert_eq!(parsed[1].hour(), 21);
-        assert_eq!(parsed[1].minute(), 12);
-        assert_eq!(parsed[1].second(), 15);
+        assert_eq!(parsed[0].timestamp.year(), 2025);
+        assert_eq!(parsed[0].timestamp.month(), 7);
+        assert_eq!(parsed[0].timestamp.day(), 14);
+        assert_eq!(parsed[0].timestamp.hour(), 21);
+        assert_eq!(parsed[0].timestamp.minute(), 12);
+        assert_eq!(parsed[0].timestamp.second(), 15);
+        assert_eq!(parsed[0].kind, ActivityKind::Push);
+        assert_eq!(parsed[0].actor.as_deref(), Some("dawe"));
+        assert_eq!(parsed[0].git_ref.as_deref(), Some("refs/heads/main"));
+
+        assert_eq!(parsed[1].timestamp.year(), 2025);
+        assert_eq!(parsed[1].timestamp.month(), 7);
+        assert_eq!(parsed[1].timestamp.day(), 14);
+        assert_eq!(parsed[1].timestamp.hour(), 21);
+        assert_eq!(parsed[1].timestamp.minute(), 12);
+        assert_eq!(parsed[1].timestamp.second(), 15);
+        assert_eq!(parsed[1].kind, ActivityKind::Merge);
+        assert_eq!(parsed[1].message.as_deref(), Some("Add gitlab support"));
     }
 }
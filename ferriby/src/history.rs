@@ -0,0 +1,349 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    activity::{Activity, ActivityKind},
+    state::state_dir,
+};
+
+/// What tells two observations of the same activity apart from different activities.
+///
+/// The ref isn't part of it, as a commit seen at the tip of a branch in one check is seen
+/// without a branch further down in the next one.
+type ActivityKey = (DateTime<Utc>, ActivityKind, Option<String>, Option<String>);
+
+fn activity_key(activity: &Activity) -> ActivityKey {
+    (
+        activity.timestamp,
+        activity.kind.clone(),
+        activity.actor.clone(),
+        activity.message.clone(),
+    )
+}
+
+/// A time in which the ferris of a source went hungry, as nothing happened for too long.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HungrySpell {
+    /// When the ferris got hungry.
+    pub start: DateTime<Utc>,
+    /// When the ferris was fed again, if it was.
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl HungrySpell {
+    /// How long the spell lasted or lasts until now.
+    pub fn duration(&self, now: DateTime<Utc>) -> TimeDelta {
+        self.end.unwrap_or(now) - self.start
+    }
+}
+
+/// All activities observed in a source over time, oldest first and without duplicates.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    activities: Vec<Activity>,
+    seen: HashSet<ActivityKey>,
+}
+
+impl History {
+    /// A history of the given activities that forgets the ones before `since`, except for the
+    /// newest of them, which still tells since when the ferris is hungry.
+    fn new(activities: Vec<Activity>, since: DateTime<Utc>) -> Self {
+        let mut seen = HashSet::new();
+        let mut activities: Vec<Activity> = activities
+            .into_iter()
+            .filter(|activity| seen.insert(activity_key(activity)))
+            .collect();
+        activities.sort_by_key(|activity| activity.timestamp);
+        let forgotten = activities
+            .partition_point(|activity| activity.timestamp < since)
+            .saturating_sub(1);
+        activities.drain(..forgotten);
+        Self {
+            seen: activities.iter().map(activity_key).collect(),
+            activities,
+        }
+    }
+
+    /// The observed activities, oldest first.
+    pub fn activities(&self) -> &[Activity] {
+        &self.activities
+    }
+
+    /// Adds the given activities and returns the ones not observed before, oldest first.
+    pub fn record(&mut self, activities: &[Activity]) -> Vec<Activity> {
+        let mut new: Vec<Activity> = activities
+            .iter()
+            .filter(|activity| self.seen.insert(activity_key(activity)))
+            .cloned()
+            .collect();
+        new.sort_by_key(|activity| activity.timestamp);
+        // new activities are usually newer than all others, which keeps the order as it is
+        let in_order = match (self.activities.last(), new.first()) {
+            (Some(last), Some(first)) => last.timestamp <= first.timestamp,
+            _ => true,
+        };
+        self.activities.extend(new.iter().cloned());
+        if !in_order {
+            self.activities.sort_by_key(|activity| activity.timestamp);
+        }
+        new
+    }
+
+    /// The last time nothing happened for longer than `hungry_after`.
+    pub fn last_hungry_spell(
+        &self,
+        hungry_after: TimeDelta,
+        now: DateTime<Utc>,
+    ) -> Option<HungrySpell> {
+        let last = self.activities.last()?;
+        if now - last.timestamp > hungry_after {
+            return Some(HungrySpell {
+                start: last.timestamp + hungry_after,
+                end: None,
+            });
+        }
+        self.activities
+            .windows(2)
+            .rev()
+            .find(|pair| pair[1].timestamp - pair[0].timestamp > hungry_after)
+            .map(|pair| HungrySpell {
+                start: pair[0].timestamp + hungry_after,
+                end: Some(pair[1].timestamp),
+            })
+    }
+}
+
+/// A line of the history file.
+#[derive(Debug, Serialize, Deserialize)]
+struct HistoryEntry {
+    source: String,
+    #[serde(flatten)]
+    activity: Activity,
+}
+
+/// The file the histories of the sources are appended to, one JSON object per line.
+#[derive(Debug, Clone)]
+pub struct HistoryFile {
+    path: PathBuf,
+}
+
+impl HistoryFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// The history file in the [`state_dir`].
+    pub fn default_path() -> Option<PathBuf> {
+        state_dir().map(|dir| dir.join("history.jsonl"))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Loads the histories keyed by the names of the sources, skipping broken lines, the
+    /// activities `counts` rejects for their source and forgetting the activities before
+    /// `since` like [`History::new`].
+    ///
+    /// The file is rewritten without the broken lines and duplicates, but keeps the skipped and
+    /// forgotten activities, as it's the durable log of all of them.
+    pub fn load(
+        &self,
+        since: DateTime<Utc>,
+        counts: impl Fn(&str, &Activity) -> bool,
+    ) -> HashMap<String, History> {
+        let Ok(content) = fs::read_to_string(&self.path) else {
+            return HashMap::new();
+        };
+        let mut activities: HashMap<String, Vec<Activity>> = HashMap::new();
+        let mut lines = 0;
+        for line in content.lines() {
+            lines += 1;
+            if let Ok(entry) = serde_json::from_str::<HistoryEntry>(line) {
+                activities
+                    .entry(entry.source)
+                    .or_default()
+                    .push(entry.activity);
+            }
+        }
+        let complete: HashMap<String, History> = activities
+            .into_iter()
+            .map(|(source, activities)| {
+                (source, History::new(activities, DateTime::<Utc>::MIN_UTC))
+            })
+            .collect();
+
+        let kept: usize = complete
+            .values()
+            .map(|history| history.activities.len())
+            .sum();
+        if kept < lines {
+            // the history is a nice to have, so failing to compact it doesn't fail the start
+            let _ = self.rewrite(&complete);
+        }
+        complete
+            .into_iter()
+            .map(|(source, history)| {
+                let activities = history
+                    .activities
+                    .into_iter()
+                    .filter(|activity| counts(&source, activity))
+                    .collect();
+                (source, History::new(activities, since))
+            })
+            .collect()
+    }
+
+    /// Replaces the content of the file by the given histories.
+    fn rewrite(&self, histories: &HashMap<String, History>) -> io::Result<()> {
+        let mut sources: Vec<&String> = histories.keys().collect();
+        sources.sort();
+        let mut lines = String::new();
+        for source in sources {
+            lines.push_str(&history_lines(source, &histories[source].activities)?);
+        }
+        // write to a temporary file first to not lose the history when interrupted
+        let tmp_path = self.path.with_extension("jsonl.tmp");
+        fs::write(&tmp_path, lines)?;
+        fs::rename(tmp_path, &self.path)
+    }
+
+    /// Appends the given activities of a source.
+    pub fn append(&self, source: &str, activities: &[Activity]) -> io::Result<()> {
+        if activities.is_empty() {
+            return Ok(());
+        }
+        let lines = history_lines(source, activities)?;
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(lines.as_bytes())
+    }
+}
+
+/// The lines of the history file for the given activities of a source.
+fn history_lines(source: &str, activities: &[Activity]) -> io::Result<String> {
+    let mut lines = String::new();
+    for activity in activities {
+        let entry = HistoryEntry {
+            source: source.to_string(),
+            activity: activity.clone(),
+        };
+        lines.push_str(&serde_json::to_string(&entry)?);
+        lines.push('\n');
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_at(timestamp: DateTime<Utc>) -> Activity {
+        Activity {
+            timestamp,
+            kind: ActivityKind::Push,
+            actor: Some("dawe".into()),
+            email: None,
+            git_ref: Some("refs/heads/main".into()),
+            message: None,
+        }
+    }
+
+    #[test]
+    fn repeated_observations_are_recorded_once() {
+        let now = Utc::now();
+        let older = push_at(now - TimeDelta::hours(2));
+        let newer = push_at(now - TimeDelta::hours(1));
+        let mut history = History::default();
+
+        assert_eq!(history.record(&[newer.clone(), older.clone()]).len(), 2);
+        let below_tip = Activity {
+            git_ref: None,
+            ..newer.clone()
+        };
+        assert!(history.record(&[below_tip, older.clone()]).is_empty());
+        assert_eq!(history.activities(), &[older, newer]);
+    }
+
+    #[test]
+    fn histories_are_appended_and_loaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let history_file = HistoryFile::new(dir.path().join("history.jsonl"));
+        let activity = push_at(DateTime::from_timestamp(1_750_000_000, 0).unwrap());
+        history_file
+            .append("github: a/b", std::slice::from_ref(&activity))
+            .unwrap();
+        history_file
+            .append("github: a/b", std::slice::from_ref(&activity))
+            .unwrap();
+        history_file.append("git: c", &[]).unwrap();
+
+        let histories = history_file.load(DateTime::UNIX_EPOCH, |_, _| true);
+        assert_eq!(histories.len(), 1);
+        assert_eq!(histories["github: a/b"].activities(), &[activity]);
+    }
+
+    #[test]
+    fn old_activities_are_forgotten_on_load_but_kept_in_the_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let history_file = HistoryFile::new(path.clone());
+        let now = Utc::now();
+        let activities: Vec<Activity> = [300, 200, 100, 1]
+            .into_iter()
+            .map(|days| push_at(now - TimeDelta::days(days)))
+            .collect();
+        history_file.append("git: a", &activities).unwrap();
+        history_file.append("git: a", &activities[3..]).unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{ broken\n")
+            .unwrap();
+
+        let since = now - TimeDelta::days(150);
+        let histories = history_file.load(since, |_, _| true);
+        // the newest activity before `since` is kept to know since when the ferris is hungry
+        assert_eq!(histories["git: a"].activities(), &activities[1..]);
+        // but the log keeps them all, only without duplicates and broken lines
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 4);
+        assert_eq!(
+            history_file.load(since, |_, _| true)["git: a"].activities(),
+            histories["git: a"].activities()
+        );
+    }
+
+    #[test]
+    fn last_hungry_spell_is_found() {
+        let now = Utc::now();
+        let hungry_after = TimeDelta::days(7);
+        let mut history = History::default();
+        assert_eq!(history.last_hungry_spell(hungry_after, now), None);
+
+        let fed = now - TimeDelta::days(10);
+        history.record(&[push_at(now - TimeDelta::days(30)), push_at(fed)]);
+        let spell = history.last_hungry_spell(hungry_after, now).unwrap();
+        assert_eq!(spell.start, fed + hungry_after);
+        assert_eq!(spell.end, None);
+        assert_eq!(spell.duration(now), TimeDelta::days(3));
+
+        history.record(&[push_at(now - TimeDelta::days(1))]);
+        let spell = history.last_hungry_spell(hungry_after, now).unwrap();
+        assert_eq!(spell.end, Some(now - TimeDelta::days(1)));
+        assert_eq!(spell.duration(now), TimeDelta::days(2));
+    }
+}
@@ -0,0 +1,293 @@
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// The things a key can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    Next,
+    Previous,
+    First,
+    Last,
+    Refresh,
+    Grid,
+    Theme,
+    Help,
+}
+
+impl Action {
+    /// All actions, in the order they are listed in the help.
+    pub const ALL: [Action; 9] = [
+        Action::Next,
+        Action::Previous,
+        Action::First,
+        Action::Last,
+        Action::Refresh,
+        Action::Grid,
+        Action::Theme,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    /// The name of the action in the config file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Next => "next",
+            Action::Previous => "previous",
+            Action::First => "first",
+            Action::Last => "last",
+            Action::Refresh => "refresh",
+            Action::Grid => "grid",
+            Action::Theme => "theme",
+            Action::Help => "help",
+        }
+    }
+
+    /// What the action does, as shown in the help.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Exit",
+            Action::Next => "Next source",
+            Action::Previous => "Previous source",
+            Action::First => "First source",
+            Action::Last => "Last source",
+            Action::Refresh => "Check the selected source now",
+            Action::Grid => "Switch between list and grid",
+            Action::Theme => "Next theme",
+            Action::Help => "Show or hide this help",
+        }
+    }
+
+    /// The action with the given name in the config file.
+    pub fn by_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::Quit => &["q", "esc", "ctrl-c"],
+            Action::Next => &["down", "j"],
+            Action::Previous => &["up", "k"],
+            Action::First => &["home", "g"],
+            Action::Last => &["end", "G"],
+            Action::Refresh => &["r"],
+            Action::Grid => &["v"],
+            Action::Theme => &["t"],
+            Action::Help => &["?"],
+        }
+    }
+}
+
+/// A key with its modifiers, like `ctrl-c`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// The key of a key event, ignoring the shift modifier of characters as it's already
+    /// part of the character.
+    fn from_event(event: &KeyEvent) -> Self {
+        let mut key = Self {
+            code: event.code,
+            modifiers: event.modifiers,
+        };
+        if let KeyCode::Char(c) = key.code {
+            key.modifiers.remove(KeyModifiers::SHIFT);
+            if key.modifiers.contains(KeyModifiers::CONTROL) {
+                key.code = KeyCode::Char(c.to_ascii_lowercase());
+            }
+        }
+        key
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    /// Parses a character like `j` or `G`, a named key like `esc`, `up` or `f1`, optionally
+    /// prefixed by `ctrl-` and `alt-`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        loop {
+            if let Some(stripped) = rest.strip_prefix("ctrl-") {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = stripped;
+            } else if let Some(stripped) = rest.strip_prefix("alt-") {
+                modifiers |= KeyModifiers::ALT;
+                rest = stripped;
+            } else {
+                break;
+            }
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if modifiers.contains(KeyModifiers::CONTROL) => {
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_lowercase().as_str() {
+                "esc" => KeyCode::Esc,
+                "enter" => KeyCode::Enter,
+                "space" => KeyCode::Char(' '),
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key {s}")),
+                },
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            code => write!(f, "{code}"),
+        }
+    }
+}
+
+/// Which keys trigger which actions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    keys: HashMap<Action, Vec<Key>>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let keys = Action::ALL
+            .into_iter()
+            .map(|action| {
+                let keys = action
+                    .default_keys()
+                    .iter()
+                    .filter_map(|key| key.parse().ok())
+                    .collect();
+                (action, keys)
+            })
+            .collect();
+        Self { keys }
+    }
+}
+
+impl KeyBindings {
+    /// Binds the given keys to an action instead of its default ones, taking them away from
+    /// the other actions.
+    pub fn bind(&mut self, action: Action, keys: Vec<Key>) {
+        for bound in self.keys.values_mut() {
+            bound.retain(|key| !keys.contains(key));
+        }
+        self.keys.insert(action, keys);
+    }
+
+    /// The keys bound to an action.
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.keys
+            .get(&action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// The keys bound to an action, as shown to the user.
+    pub fn keys_text(&self, action: Action) -> String {
+        let keys: Vec<String> = self.keys(action).iter().map(Key::to_string).collect();
+        keys.join(", ")
+    }
+
+    /// The action bound to the key of a key event.
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        let key = Key::from_event(event);
+        Action::ALL
+            .into_iter()
+            .find(|action| self.keys(*action).contains(&key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_parsed_and_shown() {
+        for (text, shown) in [
+            ("j", "j"),
+            ("G", "G"),
+            ("?", "?"),
+            ("esc", "Esc"),
+            ("Up", "↑"),
+            ("ctrl-C", "Ctrl-c"),
+            ("alt-space", "Alt-Space"),
+            ("f5", "F5"),
+        ] {
+            let key: Key = text.parse().unwrap();
+            assert_eq!(key.to_string(), shown);
+        }
+        assert!("jj".parse::<Key>().is_err());
+        assert!("f13".parse::<Key>().is_err());
+        assert!("ctrl-".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn key_events_are_mapped_to_actions() {
+        let bindings = KeyBindings::default();
+        let event = |code, modifiers| KeyEvent::new(code, modifiers);
+
+        assert_eq!(
+            bindings.action(&event(KeyCode::Char('j'), KeyModifiers::NONE)),
+            Some(Action::Next)
+        );
+        assert_eq!(
+            bindings.action(&event(KeyCode::Char('G'), KeyModifiers::SHIFT)),
+            Some(Action::Last)
+        );
+        assert_eq!(
+            bindings.action(&event(KeyCode::Char('C'), KeyModifiers::CONTROL)),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            bindings.action(&event(KeyCode::Char('x'), KeyModifiers::NONE)),
+            None
+        );
+    }
+
+    #[test]
+    fn bound_keys_replace_defaults() {
+        let mut bindings = KeyBindings::default();
+        bindings.bind(Action::Next, vec!["k".parse().unwrap()]);
+
+        assert_eq!(bindings.keys_text(Action::Next), "k");
+        assert_eq!(bindings.keys_text(Action::Previous), "↑");
+        assert_eq!(
+            bindings.action(&KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE)),
+            Some(Action::Next)
+        );
+    }
+}
@@ -1,19 +1,43 @@
-use crate::app::{App, Source};
-use config::{Config, File, Value};
+use crate::app::{App, AppSettings, KioskSettings, Source, SourceSettings, Thresholds};
+use crate::art::ArtPack;
+use crate::event::MAX_INTERVAL_SECS;
+use crate::filter::{AuthorFilter, IgnoreRules, RefFilter};
+use crate::githoster::{ConnectionSettings, HttpSettings};
+use crate::history::HistoryFile;
+use crate::keys::{Action, Key, KeyBindings};
+use crate::state::StateFile;
+use crate::theme::Theme;
+use chrono::TimeDelta;
+use config::{Config, File, Map, Value};
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 use forgejo::ForgejoSource;
-use git::GitSource;
+use git::{GitRefs, GitSource, RefKind, TagTime};
 use github::GitHubSource;
 use gitlab::GitLabSource;
+use ratatui::style::{Color, Modifier, Style};
+use regex::Regex;
 use reqwest::Url;
 use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+pub mod activity;
 pub mod app;
+pub mod art;
+pub mod error;
 pub mod event;
+pub mod filter;
 pub mod forgejo;
 pub mod git;
 pub mod githoster;
 pub mod github;
 pub mod gitlab;
+pub mod history;
+pub mod keys;
+pub mod rhythm;
+pub mod state;
+pub mod theme;
 pub mod ui;
 
 #[tokio::main]
@@ -21,11 +45,16 @@ async fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
     let args: Vec<String> = env::args().collect();
 
-    let sources = parse_args(&args);
-    match sources {
-        Ok(sources) => {
+    let config = parse_args(&args);
+    match config {
+        Ok((sources, mut settings)) => {
+            settings.state_file = StateFile::default_path().map(StateFile::new);
+            settings.history_file = HistoryFile::default_path().map(HistoryFile::new);
+            let app = App::new(sources, settings)?;
             let terminal = ratatui::init();
-            let result = App::new(sources).run(terminal).await;
+            crossterm::execute!(io::stdout(), EnableMouseCapture)?;
+            let result = app.run(terminal).await;
+            let _ = crossterm::execute!(io::stdout(), DisableMouseCapture);
             ratatui::restore();
             result
         }
@@ -60,42 +89,71 @@ fn config_path() -> String {
         .expect("failed to determine config path")
 }
 
-fn file_configured_sources(path: &str) -> Result<Vec<Source>, String> {
+fn file_configured_sources(path: &str) -> Result<(Vec<Source>, AppSettings), String> {
     let settings = Config::builder()
         .add_source(File::with_name(path))
         .build()
         .map_err(|_| format!("failed to parse config file {path}"))?;
+    let root = settings.cache.clone().into_table().unwrap_or_default();
+    let global_settings = parse_source_settings(&root, &SourceSettings::default())?;
+    let app_settings = parse_app_settings(&root)?;
     let mut sources = vec![];
 
     let git_config = settings.get_array("git");
     if let Ok(paths) = git_config {
-        paths.iter().for_each(|path| {
-            let source = Source::Git(GitSource {
-                path: path.clone().into_string().expect("expected a string"),
-            });
-            sources.push(source);
-        })
+        for conf_val in paths.iter() {
+            let source = match conf_val.clone().into_table() {
+                Ok(table) => {
+                    let path = parse_string_key(&table, "path")?;
+                    let snacking = match table.get("snacking") {
+                        Some(value) => value
+                            .clone()
+                            .into_bool()
+                            .map_err(|_| "expected snacking to be a boolean".to_string())?,
+                        None => false,
+                    };
+                    GitSource {
+                        path,
+                        refs: parse_git_refs(&table)?,
+                        snacking,
+                        settings: parse_source_settings(&table, &global_settings)?,
+                    }
+                }
+                Err(_) => GitSource {
+                    path: conf_val
+                        .clone()
+                        .into_string()
+                        .map_err(|_| "expected a git source to be a path or a table".to_string())?,
+                    refs: GitRefs::default(),
+                    snacking: false,
+                    settings: global_settings.clone(),
+                },
+            };
+            sources.push(Source::Git(source));
+        }
     };
 
     handle_git_hoster_config(
         &settings,
+        &global_settings,
         &mut sources,
         "github",
         GH_PAT_ENV_NAME,
-        |owner, repo, pat| {
+        |owner, repo, pat, settings| {
             Source::GitHub(GitHubSource {
                 owner,
                 repo,
                 pat: pat.clone(),
+                settings,
             })
         },
-    );
+    )?;
 
     let forgejo_config = settings.get_array("forgejo");
     if let Ok(tables) = forgejo_config {
         let pat = try_get_pat(FJ_PAT_ENV_NAME);
 
-        tables.iter().for_each(|table| {
+        for table in tables.iter() {
             let pat = pat.clone();
             let table = table.clone().into_table().expect("expected a table");
             let baseurl_value = table
@@ -121,16 +179,17 @@ fn file_configured_sources(path: &str) -> Result<Vec<Source>, String> {
                 owner,
                 repo,
                 pat,
+                settings: parse_source_settings(&table, &global_settings)?,
             });
             sources.push(source);
-        })
+        }
     };
 
     let gitlab_config = settings.get_array("gitlab");
     if let Ok(tables) = gitlab_config {
         let pat = try_get_pat(GL_PAT_ENV_NAME);
 
-        tables.iter().for_each(|table| {
+        for table in tables.iter() {
             let pat = pat.clone();
             let table = table.clone().into_table().expect("expected a table");
             let hostname_value = table
@@ -162,36 +221,559 @@ fn file_configured_sources(path: &str) -> Result<Vec<Source>, String> {
                 project_id,
                 project_name,
                 pat,
+                settings: parse_source_settings(&table, &global_settings)?,
             });
             sources.push(source);
-        })
+        }
     };
 
     if sources.is_empty() {
         Err("no sources defined in config file".into())
     } else {
-        Ok(sources)
+        Ok((sources, app_settings))
     }
 }
 
+/// Reads the string under the given key of a source table.
+fn parse_string_key(table: &Map<String, Value>, key: &str) -> Result<String, String> {
+    table
+        .get(key)
+        .ok_or_else(|| format!("expected a {key} key"))?
+        .clone()
+        .into_string()
+        .map_err(|_| format!("expected {key} to be a string"))
+}
+
 fn handle_git_hoster_config<F>(
     settings: &Config,
+    global_settings: &SourceSettings,
     sources: &mut Vec<Source>,
     key: &str,
     pat_env_var: &str,
     f: F,
-) where
-    F: Fn(String, String, Option<String>) -> Source,
+) -> Result<(), String>
+where
+    F: Fn(String, String, Option<String>, SourceSettings) -> Source,
 {
     let cb_config = settings.get_array(key);
     if let Ok(repos) = cb_config {
         let pat = try_get_pat(pat_env_var);
-        repos.iter().for_each(|conf_val| {
-            let (owner, repo) = parse_owner_repo_conf_value(conf_val);
-            let s = f(owner, repo, pat.clone());
+        for conf_val in repos.iter() {
+            let (owner, repo, source_settings) = match conf_val.clone().into_table() {
+                Ok(table) => {
+                    let (owner, repo) = parse_owner_repo(&parse_string_key(&table, "repo")?);
+                    let source_settings = parse_source_settings(&table, global_settings)?;
+                    (owner, repo, source_settings)
+                }
+                Err(_) => {
+                    let (owner, repo) = parse_owner_repo_conf_value(conf_val);
+                    (owner, repo, global_settings.clone())
+                }
+            };
+            let s = f(owner, repo, pat.clone(), source_settings);
             sources.push(s);
-        })
+        }
+    };
+    Ok(())
+}
+
+/// Parses the settings of the whole application.
+fn parse_app_settings(root: &Map<String, Value>) -> Result<AppSettings, String> {
+    let http = match root.get("http") {
+        Some(value) => parse_http_settings(value)?,
+        None => HttpSettings::default(),
+    };
+    let kiosk = match root.get("kiosk") {
+        Some(value) => Some(parse_kiosk_settings(value)?),
+        None => None,
+    };
+    let theme = match root.get("theme") {
+        Some(value) => parse_theme(value)?,
+        None => Theme::default(),
+    };
+    let art = match root.get("art") {
+        Some(value) => {
+            let dir = value
+                .clone()
+                .into_string()
+                .map_err(|_| "expected art to be the path to an art pack".to_string())?;
+            ArtPack::load(Path::new(&dir))?
+        }
+        None => ArtPack::default(),
+    };
+    let keys = match root.get("keys") {
+        Some(value) => parse_key_bindings(value)?,
+        None => KeyBindings::default(),
+    };
+    Ok(AppSettings {
+        http,
+        kiosk,
+        theme,
+        art,
+        keys,
+        ..AppSettings::default()
+    })
+}
+
+/// Parses the keys of actions like `{ "next": ["j", "down"], "quit": "q" }`, replacing the
+/// default keys of the given actions.
+fn parse_key_bindings(value: &Value) -> Result<KeyBindings, String> {
+    let table = value
+        .clone()
+        .into_table()
+        .map_err(|_| "expected keys to be a table".to_string())?;
+    let mut bindings = KeyBindings::default();
+    let mut bound: Vec<(Key, Action)> = vec![];
+    for (name, value) in table {
+        let action = Action::by_name(&name).ok_or_else(|| format!("unknown action {name}"))?;
+        let names = match value.clone().into_array() {
+            Ok(values) => values,
+            Err(_) => vec![value],
+        };
+        let mut keys = vec![];
+        for name in names {
+            let key: Key = name
+                .into_string()
+                .map_err(|_| format!("expected the keys of {} to be strings", action.name()))?
+                .parse()?;
+            if let Some((_, other)) = bound.iter().find(|(bound_key, _)| *bound_key == key) {
+                return Err(format!(
+                    "key {key} is bound to both {} and {}",
+                    other.name(),
+                    action.name()
+                ));
+            }
+            bound.push((key, action));
+            keys.push(key);
+        }
+        bindings.bind(action, keys);
+    }
+    Ok(bindings)
+}
+
+/// Parses the name of a built-in theme or a custom theme like
+/// `{ "name": "mine", "extends": "light", "border": "magenta", "sad": { "fg": "#ff0000", "modifiers": ["bold"] } }`.
+fn parse_theme(value: &Value) -> Result<Theme, String> {
+    if let Ok(name) = value.clone().into_string() {
+        return Theme::by_name(&name).ok_or_else(|| format!("unknown theme {name}"));
+    }
+    let table = value
+        .clone()
+        .into_table()
+        .map_err(|_| "expected theme to be a name or a table".to_string())?;
+    let string = |key: &str| -> Result<Option<String>, String> {
+        table
+            .get(key)
+            .map(|value| {
+                value
+                    .clone()
+                    .into_string()
+                    .map_err(|_| format!("expected {key} to be a string"))
+            })
+            .transpose()
+    };
+    let extends = string("extends")?.unwrap_or_else(|| "dark".into());
+    let mut theme = Theme::by_name(&extends).ok_or_else(|| format!("unknown theme {extends}"))?;
+    theme.name = string("name")?.unwrap_or_else(|| "custom".into());
+    for (role, value) in table.iter() {
+        match role.as_str() {
+            "name" | "extends" => {}
+            "heat" => {
+                let styles = value
+                    .clone()
+                    .into_array()
+                    .ok()
+                    .filter(|styles| styles.len() == theme.heat.len())
+                    .ok_or_else(|| "expected heat to be a list of 5 styles".to_string())?;
+                for (heat, style) in theme.heat.iter_mut().zip(&styles) {
+                    *heat = parse_style(style, role)?;
+                }
+            }
+            _ => {
+                let style = parse_style(value, role)?;
+                *theme
+                    .role_mut(role)
+                    .ok_or_else(|| format!("unknown theme role {role}"))? = style;
+            }
+        }
+    }
+    Ok(theme)
+}
+
+/// Parses a style given as foreground colour or like `{ "fg": "red", "bg": "#002b36", "modifiers": ["bold"] }`.
+fn parse_style(value: &Value, role: &str) -> Result<Style, String> {
+    let color = |value: &Value| -> Result<Color, String> {
+        value
+            .clone()
+            .into_string()
+            .ok()
+            .and_then(|color| color.parse().ok())
+            .ok_or_else(|| format!("expected a colour for {role}"))
+    };
+    if value.clone().into_string().is_ok() {
+        return Ok(Style::new().fg(color(value)?));
+    }
+    let table = value
+        .clone()
+        .into_table()
+        .map_err(|_| format!("expected {role} to be a colour or a table"))?;
+    let mut style = Style::new();
+    if let Some(fg) = table.get("fg") {
+        style = style.fg(color(fg)?);
+    }
+    if let Some(bg) = table.get("bg") {
+        style = style.bg(color(bg)?);
+    }
+    if let Some(modifiers) = table.get("modifiers") {
+        let modifiers = modifiers
+            .clone()
+            .into_array()
+            .map_err(|_| format!("expected the modifiers of {role} to be a list"))?;
+        for modifier in modifiers {
+            let modifier = match modifier.into_string().as_deref() {
+                Ok("bold") => Modifier::BOLD,
+                Ok("dim") => Modifier::DIM,
+                Ok("italic") => Modifier::ITALIC,
+                Ok("underlined") => Modifier::UNDERLINED,
+                Ok("slow_blink") => Modifier::SLOW_BLINK,
+                Ok("rapid_blink") => Modifier::RAPID_BLINK,
+                Ok("reversed") => Modifier::REVERSED,
+                Ok("hidden") => Modifier::HIDDEN,
+                Ok("crossed_out") => Modifier::CROSSED_OUT,
+                _ => return Err(format!("unknown modifier for {role}")),
+            };
+            style = style.add_modifier(modifier);
+        }
+    }
+    Ok(style)
+}
+
+/// Parses kiosk settings like `{ "interval": 10, "skip_buzzing": true }`.
+fn parse_kiosk_settings(value: &Value) -> Result<KioskSettings, String> {
+    let table = value
+        .clone()
+        .into_table()
+        .map_err(|_| "expected kiosk to be a table".to_string())?;
+    let interval_secs = parse_interval(
+        table
+            .get("interval")
+            .and_then(|v| v.clone().into_float().ok()),
+    )?;
+    let skip_buzzing = match table.get("skip_buzzing") {
+        Some(value) => value
+            .clone()
+            .into_bool()
+            .map_err(|_| "expected skip_buzzing to be a boolean".to_string())?,
+        None => false,
+    };
+    Ok(KioskSettings {
+        interval_secs,
+        skip_buzzing,
+    })
+}
+
+/// Parses http settings like `{ "connect_timeout": 10, "read_timeout": 30, "retries": 2, "retry_delay": 0.5 }`.
+fn parse_http_settings(value: &Value) -> Result<HttpSettings, String> {
+    let table = value
+        .clone()
+        .into_table()
+        .map_err(|_| "expected http to be a table".to_string())?;
+    let defaults = HttpSettings::default();
+    let secs = |key: &str, default: Duration| -> Result<Duration, String> {
+        match table.get(key) {
+            Some(value) => value
+                .clone()
+                .into_float()
+                .ok()
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .ok_or_else(|| format!("expected {key} to be a number of seconds")),
+            None => Ok(default),
+        }
+    };
+    let retries = match table.get("retries") {
+        Some(value) => value
+            .clone()
+            .into_uint()
+            .ok()
+            .and_then(|retries| u32::try_from(retries).ok())
+            .ok_or_else(|| "expected retries to be a non-negative number".to_string())?,
+        None => defaults.retries,
+    };
+    Ok(HttpSettings {
+        connect_timeout: secs("connect_timeout", defaults.connect_timeout)?,
+        read_timeout: secs("read_timeout", defaults.read_timeout)?,
+        retries,
+        retry_delay: secs("retry_delay", defaults.retry_delay)?,
+    })
+}
+
+/// Parses the kinds of refs of a git source like `"refs": ["local", "remote", "tags"]` and the
+/// `tag_time` of annotated tags, `tagger` or `commit`.
+fn parse_git_refs(table: &Map<String, Value>) -> Result<GitRefs, String> {
+    let mut refs = GitRefs::default();
+    if let Some(value) = table.get("refs") {
+        refs.kinds = value
+            .clone()
+            .into_array()
+            .and_then(|values| values.into_iter().map(Value::into_string).collect())
+            .map_err(|_| "expected refs to be an array of strings".to_string())
+            .and_then(|names: Vec<String>| {
+                names
+                    .iter()
+                    .map(|name| {
+                        RefKind::by_name(name).ok_or_else(|| format!("unknown kind of refs {name}"))
+                    })
+                    .collect()
+            })?;
+    }
+    if let Some(value) = table.get("tag_time") {
+        refs.tag_time = match value.clone().into_string().as_deref() {
+            Ok("tagger") => TagTime::Tagger,
+            Ok("commit") => TagTime::Commit,
+            _ => return Err("expected tag_time to be tagger or commit".into()),
+        };
+    }
+    Ok(refs)
+}
+
+/// Parses the settings every kind of source can have, falling back to `defaults` for missing keys.
+fn parse_source_settings(
+    table: &Map<String, Value>,
+    defaults: &SourceSettings,
+) -> Result<SourceSettings, String> {
+    let thresholds = match table.get("thresholds") {
+        Some(value) => parse_thresholds(value, &defaults.thresholds)?,
+        None => defaults.thresholds,
+    };
+    let interval_secs = match table.get("interval") {
+        Some(value) => Some(parse_interval(value.clone().into_float().ok())?),
+        None => defaults.interval_secs,
+    };
+    let branches = match table.get("branches") {
+        Some(value) => parse_ref_filter(value)?,
+        None => defaults.branches.clone(),
+    };
+    let authors = match table.get("authors") {
+        Some(value) => parse_author_filter(value)?,
+        None => defaults.authors.clone(),
+    };
+    let ignore = match table.get("ignore") {
+        Some(value) => parse_ignore_rules(value, &defaults.ignore)?,
+        None => defaults.ignore.clone(),
+    };
+    Ok(SourceSettings {
+        thresholds,
+        interval_secs,
+        connection: parse_connection_settings(table, &defaults.connection)?,
+        branches,
+        authors,
+        ignore,
+    })
+}
+
+/// Parses which activities don't count like
+/// `{ "merges": true, "empty": true, "bots": ["/\\[bot\\]$/"], "messages": ["^fixup!"] }`.
+fn parse_ignore_rules(value: &Value, defaults: &IgnoreRules) -> Result<IgnoreRules, String> {
+    let table = value
+        .clone()
+        .into_table()
+        .map_err(|_| "expected ignore to be a table".to_string())?;
+    let flag = |key: &str, default: bool| -> Result<bool, String> {
+        match table.get(key) {
+            Some(value) => value
+                .clone()
+                .into_bool()
+                .map_err(|_| format!("expected ignore.{key} to be a boolean")),
+            None => Ok(default),
+        }
+    };
+    let strings = |key: &str| -> Result<Option<Vec<String>>, String> {
+        table
+            .get(key)
+            .map(|value| {
+                value
+                    .clone()
+                    .into_array()
+                    .and_then(|values| values.into_iter().map(Value::into_string).collect())
+                    .map_err(|_| format!("expected ignore.{key} to be an array of strings"))
+            })
+            .transpose()
+    };
+    if let Some(key) = table
+        .keys()
+        .find(|key| !["merges", "empty", "bots", "messages"].contains(&key.as_str()))
+    {
+        return Err(format!("unknown key ignore.{key}"));
+    }
+    Ok(IgnoreRules {
+        merges: flag("merges", defaults.merges)?,
+        empty: flag("empty", defaults.empty)?,
+        bots: match strings("bots")? {
+            Some(patterns) => patterns
+                .iter()
+                .map(|pattern| pattern.parse())
+                .collect::<Result<_, _>>()?,
+            None => defaults.bots.clone(),
+        },
+        messages: match strings("messages")? {
+            Some(regexes) => regexes
+                .iter()
+                .map(|regex| {
+                    Regex::new(regex).map_err(|e| format!("invalid message regex {regex}: {e}"))
+                })
+                .collect::<Result<_, _>>()?,
+            None => defaults.messages.clone(),
+        },
+    })
+}
+
+/// Parses the names, email addresses, usernames and `/regexes/` of the authors to look at.
+fn parse_author_filter(value: &Value) -> Result<AuthorFilter, String> {
+    let patterns: Vec<String> = value
+        .clone()
+        .into_array()
+        .and_then(|values| values.into_iter().map(Value::into_string).collect())
+        .map_err(|_| "expected authors to be an array of strings".to_string())?;
+    Ok(AuthorFilter {
+        patterns: patterns
+            .iter()
+            .map(|pattern| pattern.parse())
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+/// Parses the glob patterns of the refs to look at like
+/// `{ "include": ["main", "release/*"], "exclude": ["dependabot/*"] }`.
+fn parse_ref_filter(value: &Value) -> Result<RefFilter, String> {
+    let table = value
+        .clone()
+        .into_table()
+        .map_err(|_| "expected branches to be a table".to_string())?;
+    let patterns = |key: &str| -> Result<Vec<String>, String> {
+        match table.get(key) {
+            Some(value) => value
+                .clone()
+                .into_array()
+                .and_then(|values| values.into_iter().map(Value::into_string).collect())
+                .map_err(|_| format!("expected branches.{key} to be an array of strings")),
+            None => Ok(vec![]),
+        }
+    };
+    if let Some(key) = table
+        .keys()
+        .find(|key| !["include", "exclude"].contains(&key.as_str()))
+    {
+        return Err(format!("unknown key branches.{key}"));
+    }
+    Ok(RefFilter {
+        include: patterns("include")?,
+        exclude: patterns("exclude")?,
+    })
+}
+
+/// Parses the proxy and TLS settings, falling back to `defaults` for missing keys.
+fn parse_connection_settings(
+    table: &Map<String, Value>,
+    defaults: &ConnectionSettings,
+) -> Result<ConnectionSettings, String> {
+    let string = |key: &str| -> Result<Option<String>, String> {
+        table
+            .get(key)
+            .map(|value| {
+                value
+                    .clone()
+                    .into_string()
+                    .map_err(|_| format!("expected {key} to be a string"))
+            })
+            .transpose()
+    };
+    let strings = |key: &str| -> Result<Option<Vec<String>>, String> {
+        table
+            .get(key)
+            .map(|value| {
+                value
+                    .clone()
+                    .into_array()
+                    .and_then(|values| values.into_iter().map(Value::into_string).collect())
+                    .map_err(|_| format!("expected {key} to be an array of strings"))
+            })
+            .transpose()
+    };
+
+    // the certificate and its key belong together, so they are taken from the same place
+    let client_certificate = string("client_certificate")?.map(PathBuf::from);
+    let client_key = string("client_key")?.map(PathBuf::from);
+    if client_certificate.is_some() != client_key.is_some() {
+        return Err("client_certificate and client_key must be given together".into());
+    }
+    let (client_certificate, client_key) = if client_certificate.is_some() {
+        (client_certificate, client_key)
+    } else {
+        (
+            defaults.client_certificate.clone(),
+            defaults.client_key.clone(),
+        )
+    };
+
+    let connection = ConnectionSettings {
+        proxy: string("proxy")?.or_else(|| defaults.proxy.clone()),
+        no_proxy: strings("no_proxy")?.unwrap_or_else(|| defaults.no_proxy.clone()),
+        root_certificates: strings("root_certificates")?
+            .map(|paths| paths.into_iter().map(PathBuf::from).collect())
+            .unwrap_or_else(|| defaults.root_certificates.clone()),
+        client_certificate,
+        client_key,
+        insecure_localhost: match table.get("insecure_localhost") {
+            Some(value) => value
+                .clone()
+                .into_bool()
+                .map_err(|_| "expected insecure_localhost to be a bool".to_string())?,
+            None => defaults.insecure_localhost,
+        },
     };
+    Ok(connection)
+}
+
+/// Checks that an interval in seconds is a positive number of at most [`MAX_INTERVAL_SECS`].
+fn parse_interval(secs: Option<f64>) -> Result<f32, String> {
+    match secs {
+        Some(secs) if secs > 0.0 && secs <= f64::from(MAX_INTERVAL_SECS) => Ok(secs as f32),
+        _ => Err(format!(
+            "expected the interval to be a positive number of seconds up to {MAX_INTERVAL_SECS}"
+        )),
+    }
+}
+
+/// The longest threshold in hours, a hundred years, to keep the time deltas in range.
+const MAX_THRESHOLD_HOURS: f64 = 100.0 * 365.0 * 24.0;
+
+/// Parses thresholds given in hours like `{ "buzzing": 24, "okayish": 168 }`.
+fn parse_thresholds(value: &Value, defaults: &Thresholds) -> Result<Thresholds, String> {
+    let table = value
+        .clone()
+        .into_table()
+        .map_err(|_| "expected thresholds to be a table".to_string())?;
+    let hours = |key: &str, default: TimeDelta| -> Result<TimeDelta, String> {
+        match table.get(key) {
+            Some(value) => {
+                let hours = value
+                    .clone()
+                    .into_float()
+                    .map_err(|_| format!("expected the {key} threshold to be a number of hours"))?;
+                if !hours.is_finite() || hours.abs() > MAX_THRESHOLD_HOURS {
+                    return Err(format!(
+                        "expected the {key} threshold to be at most {MAX_THRESHOLD_HOURS} hours"
+                    ));
+                }
+                TimeDelta::try_seconds((hours * 3600.0) as i64)
+                    .ok_or_else(|| format!("the {key} threshold is out of range"))
+            }
+            None => Ok(default),
+        }
+    };
+    let buzzing = hours("buzzing", defaults.buzzing())?;
+    let okayish = hours("okayish", defaults.okayish())?;
+    Thresholds::new(buzzing, okayish).map_err(|e| format!("invalid thresholds: {e}"))
 }
 
 fn try_get_pat(env_var: &str) -> Option<String> {
@@ -223,7 +805,7 @@ fn parse_owner_repo_conf_value(conf_val: &Value) -> (String, String) {
     parse_owner_repo(&val)
 }
 
-fn parse_args(args: &[String]) -> Result<Vec<Source>, String> {
+fn parse_args(args: &[String]) -> Result<(Vec<Source>, AppSettings), String> {
     if args.len() <= 1 {
         let path = config_path();
         file_configured_sources(path.as_str())
@@ -233,6 +815,7 @@ fn parse_args(args: &[String]) -> Result<Vec<Source>, String> {
     } else {
         let chunks = args[1..].chunks(2);
         let mut sources = vec![];
+        let mut settings = AppSettings::default();
         for chunk in chunks {
             if chunk.len() != 2 {
                 return Err("argument missing".into());
@@ -244,7 +827,12 @@ fn parse_args(args: &[String]) -> Result<Vec<Source>, String> {
                     _ => None,
                 };
                 let (owner, repo) = parse_owner_repo(&chunk[1]);
-                let source = GitHubSource { owner, repo, pat };
+                let source = GitHubSource {
+                    owner,
+                    repo,
+                    pat,
+                    settings: SourceSettings::default(),
+                };
                 sources.push(Source::GitHub(source));
             } else if chunk[0] == "-gl" {
                 let pat = match std::env::var(GL_PAT_ENV_NAME) {
@@ -260,6 +848,7 @@ fn parse_args(args: &[String]) -> Result<Vec<Source>, String> {
                     project_id: parts[1].to_string(),
                     project_name: parts[2].to_string(),
                     pat,
+                    settings: SourceSettings::default(),
                 };
                 sources.push(Source::GitLab(source));
             } else if chunk[0] == "-fj" {
@@ -273,13 +862,30 @@ fn parse_args(args: &[String]) -> Result<Vec<Source>, String> {
                     owner,
                     repo,
                     pat,
+                    settings: SourceSettings::default(),
                 };
                 sources.push(Source::Forgejo(source));
             } else if chunk[0] == "-g" {
                 let source = GitSource {
                     path: chunk[1].clone(),
+                    refs: GitRefs::default(),
+                    snacking: false,
+                    settings: SourceSettings::default(),
                 };
                 sources.push(Source::Git(source));
+            } else if chunk[0] == "-i" {
+                let secs = parse_interval(chunk[1].parse().ok())?;
+                match sources.last_mut() {
+                    Some(source) => source.settings_mut().interval_secs = Some(secs),
+                    None => return Err("-i arg needs to follow a source".into()),
+                }
+            } else if chunk[0] == "-k" || chunk[0] == "-ku" {
+                settings.kiosk = Some(KioskSettings {
+                    interval_secs: parse_interval(chunk[1].parse().ok())?,
+                    skip_buzzing: chunk[0] == "-ku",
+                });
+            } else if chunk[0] == "-a" {
+                settings.art = ArtPack::load(Path::new(&chunk[1]))?;
             } else if chunk[0] == "-c" {
                 return Err("-c arg can't be combined with other args".into());
             } else {
@@ -287,13 +893,16 @@ fn parse_args(args: &[String]) -> Result<Vec<Source>, String> {
             };
         }
 
-        Ok(sources)
+        if sources.is_empty() {
+            return Err("no sources given".into());
+        }
+        Ok((sources, settings))
     }
 }
 
 fn usage() -> ! {
     eprintln!(
-        "Usage: ferriby [-c config_file] | [-g path_to_repo] [-gh owner/repository] [-fj base_url/owner/repository] [-gl hostname/projectid/projectname]"
+        "Usage: ferriby [-c config_file] | [-g path_to_repo] [-gh owner/repository] [-fj base_url/owner/repository] [-gl hostname/projectid/projectname] [-i interval_secs_of_previous_source] [-k secs_per_source | -ku secs_per_source_not_buzzing] [-a art_pack_dir]"
     );
     std::process::exit(1);
 }
@@ -332,6 +941,13 @@ mod tests {
         assert!(sources.is_err());
     }
 
+    #[test]
+    fn parse_args_returns_err_without_sources() {
+        let args = vec!["ferriby".into(), "-k".into(), "10".into()];
+        let sources = parse_args(&args);
+        assert!(sources.is_err());
+    }
+
     #[test]
     fn parse_args_returns_sources() {
         let args = vec![
@@ -348,22 +964,17 @@ mod tests {
         let sources = parse_args(&args);
 
         assert!(sources.is_ok());
-        let sources = sources.unwrap();
+        let (sources, _) = sources.unwrap();
         assert_eq!(sources.len(), 4);
 
-        if let Source::GitHub(GitHubSource {
-            owner,
-            repo,
-            pat: _,
-        }) = &sources[0]
-        {
+        if let Source::GitHub(GitHubSource { owner, repo, .. }) = &sources[0] {
             assert_eq!(owner, "owner1");
             assert_eq!(repo, "repo1");
         } else {
             panic!("unexpected source");
         }
 
-        if let Source::Git(GitSource { path }) = &sources[1] {
+        if let Source::Git(GitSource { path, .. }) = &sources[1] {
             assert_eq!(path, "dir1/repo2");
         } else {
             panic!("unexpected source");
@@ -373,7 +984,7 @@ mod tests {
             base_url,
             owner,
             repo,
-            pat: _,
+            ..
         }) = &sources[2]
         {
             assert_eq!(base_url.as_str(), "https://codeberg.org/");
@@ -387,7 +998,7 @@ mod tests {
             hostname,
             project_id,
             project_name,
-            pat: _,
+            ..
         }) = &sources[3]
         {
             assert_eq!(hostname, "gitlab.com");
@@ -462,7 +1073,7 @@ mod tests {
         let path = temp_file.path().to_str().unwrap();
         let sources = file_configured_sources(path);
         match sources {
-            Ok(sources) => {
+            Ok((sources, _)) => {
                 assert_eq!(sources.len(), 8);
                 let g1_find = sources
                     .iter()
@@ -519,4 +1130,523 @@ mod tests {
             env::remove_var(key);
         }
     }
+
+    fn write_config(config: &str) -> NamedTempFile {
+        let mut temp_file = tempfile::Builder::new()
+            .suffix(".json")
+            .tempfile()
+            .expect("NamedTempFile::new() failed");
+        temp_file
+            .write_all(config.as_bytes())
+            .expect("write_all failed");
+        temp_file.flush().expect("flush failed");
+        temp_file
+    }
+
+    #[test]
+    fn config_file_thresholds_are_global_and_per_source() {
+        let config = "{ \
+                \"thresholds\": { \"buzzing\": 48, \"okayish\": 240 }, \
+                \"git\": [ \
+                    \"foo/bar/baz\", \
+                    { \"path\": \"mi/mu/meh\", \"thresholds\": { \"okayish\": 720 } } \
+                ], \
+                \"github\": [ \
+                    { \"repo\": \"gh_owner1/gh_repo1\", \"thresholds\": { \"buzzing\": 1, \"okayish\": 2 } } \
+                ] \
+            }";
+        let temp_file = write_config(config);
+        let path = temp_file.path().to_str().unwrap();
+        let (sources, _) = file_configured_sources(path).unwrap();
+
+        let thresholds: Vec<_> = sources
+            .iter()
+            .map(|source| source.settings().thresholds)
+            .collect();
+        assert_eq!(
+            thresholds[0],
+            Thresholds::new(TimeDelta::hours(48), TimeDelta::hours(240)).unwrap()
+        );
+        assert_eq!(
+            thresholds[1],
+            Thresholds::new(TimeDelta::hours(48), TimeDelta::hours(720)).unwrap()
+        );
+        assert_eq!(
+            thresholds[2],
+            Thresholds::new(TimeDelta::hours(1), TimeDelta::hours(2)).unwrap()
+        );
+    }
+
+    #[test]
+    fn config_file_with_non_monotonic_thresholds_should_err() {
+        let config = "{ \
+                \"git\": [ \
+                    { \"path\": \"mi/mu/meh\", \"thresholds\": { \"buzzing\": 200 } } \
+                ] \
+            }";
+        let temp_file = write_config(config);
+        let path = temp_file.path().to_str().unwrap();
+        let sources = file_configured_sources(path);
+        assert!(sources.is_err());
+    }
+
+    #[test]
+    fn config_file_with_source_tables_missing_keys_should_err() {
+        for config in [
+            "{ \"git\": [ { \"snacking\": true } ] }",
+            "{ \"git\": [ { \"path\": [] } ] }",
+            "{ \"github\": [ { \"interval\": 30 } ] }",
+        ] {
+            let temp_file = write_config(config);
+            let path = temp_file.path().to_str().unwrap();
+            assert!(file_configured_sources(path).is_err(), "{config}");
+        }
+    }
+
+    #[test]
+    fn config_file_with_huge_thresholds_should_err() {
+        let temp_file =
+            write_config("{ \"thresholds\": { \"okayish\": 1e13 }, \"git\": [ \"foo/bar/baz\" ] }");
+        let path = temp_file.path().to_str().unwrap();
+        assert!(file_configured_sources(path).is_err());
+    }
+
+    #[test]
+    fn parse_args_applies_interval_to_previous_source() {
+        let args = vec![
+            "ferriby".into(),
+            "-g".into(),
+            "dir1/repo1".into(),
+            "-gh".into(),
+            "owner1/repo2".into(),
+            "-i".into(),
+            "30".into(),
+        ];
+        let (sources, _) = parse_args(&args).unwrap();
+
+        assert_eq!(sources[0].settings().interval_secs, None);
+        assert_eq!(sources[1].settings().interval_secs, Some(30.0));
+    }
+
+    #[test]
+    fn parse_args_returns_err_for_misplaced_or_invalid_interval() {
+        let args = vec!["ferriby".into(), "-i".into(), "30".into()];
+        assert!(parse_args(&args).is_err());
+
+        let args = vec![
+            "ferriby".into(),
+            "-g".into(),
+            "dir1/repo1".into(),
+            "-i".into(),
+            "-5".into(),
+        ];
+        assert!(parse_args(&args).is_err());
+
+        for secs in ["1e40", "inf", "NaN", "604801"] {
+            let args = vec![
+                "ferriby".into(),
+                "-g".into(),
+                "dir1/repo1".into(),
+                "-i".into(),
+                secs.into(),
+            ];
+            assert!(parse_args(&args).is_err(), "{secs}");
+        }
+    }
+
+    #[test]
+    fn parse_args_returns_kiosk_settings() {
+        let args = vec![
+            "ferriby".into(),
+            "-g".into(),
+            "dir1/repo1".into(),
+            "-ku".into(),
+            "15".into(),
+        ];
+        let (_, settings) = parse_args(&args).unwrap();
+        assert_eq!(
+            settings.kiosk,
+            Some(KioskSettings {
+                interval_secs: 15.0,
+                skip_buzzing: true,
+            })
+        );
+
+        let args = vec![
+            "ferriby".into(),
+            "-g".into(),
+            "dir1/repo1".into(),
+            "-k".into(),
+            "0".into(),
+        ];
+        assert!(parse_args(&args).is_err());
+    }
+
+    #[test]
+    fn config_file_kiosk_settings_are_parsed() {
+        let config = "{ \
+                \"kiosk\": { \"interval\": 20 }, \
+                \"git\": [ \"foo/bar/baz\" ] \
+            }";
+        let temp_file = write_config(config);
+        let path = temp_file.path().to_str().unwrap();
+        let (_, settings) = file_configured_sources(path).unwrap();
+        assert_eq!(
+            settings.kiosk,
+            Some(KioskSettings {
+                interval_secs: 20.0,
+                skip_buzzing: false,
+            })
+        );
+
+        let temp_file = write_config("{ \"kiosk\": {}, \"git\": [ \"foo/bar/baz\" ] }");
+        let path = temp_file.path().to_str().unwrap();
+        assert!(file_configured_sources(path).is_err());
+    }
+
+    #[test]
+    fn config_file_themes_are_parsed() {
+        let temp_file = write_config("{ \"theme\": \"solarized\", \"git\": [ \"foo/bar/baz\" ] }");
+        let path = temp_file.path().to_str().unwrap();
+        let (_, settings) = file_configured_sources(path).unwrap();
+        assert_eq!(settings.theme, Theme::by_name("solarized").unwrap());
+
+        let config = "{ \
+                \"theme\": { \
+                    \"extends\": \"light\", \
+                    \"border\": \"magenta\", \
+                    \"sad\": { \"fg\": \"#ff0000\", \"bg\": \"black\", \"modifiers\": [ \"bold\" ] } \
+                }, \
+                \"git\": [ \"foo/bar/baz\" ] \
+            }";
+        let temp_file = write_config(config);
+        let path = temp_file.path().to_str().unwrap();
+        let (_, settings) = file_configured_sources(path).unwrap();
+        let light = Theme::by_name("light").unwrap();
+        assert_eq!(settings.theme.name, "custom");
+        assert_eq!(settings.theme.base, light.base);
+        assert_eq!(settings.theme.border, Style::new().fg(Color::Magenta));
+        assert_eq!(
+            settings.theme.sad,
+            Style::new()
+                .fg(Color::Rgb(255, 0, 0))
+                .bg(Color::Black)
+                .add_modifier(Modifier::BOLD)
+        );
+
+        for theme in [
+            "\"neon\"",
+            "{ \"sad\": \"not a colour\" }",
+            "{ \"glow\": \"red\" }",
+        ] {
+            let config = format!("{{ \"theme\": {theme}, \"git\": [ \"foo/bar/baz\" ] }}");
+            let temp_file = write_config(&config);
+            let path = temp_file.path().to_str().unwrap();
+            assert!(file_configured_sources(path).is_err(), "{theme}");
+        }
+    }
+
+    #[test]
+    fn config_file_art_pack_is_loaded() {
+        let dir = tempfile::tempdir().unwrap();
+        for mood in ["undecided", "sad", "okayish", "buzzing"] {
+            std::fs::write(dir.path().join(format!("{mood}.txt")), "(o_o)").unwrap();
+        }
+        let config = format!(
+            "{{ \"art\": {:?}, \"git\": [ \"foo/bar/baz\" ] }}",
+            dir.path().to_str().unwrap()
+        );
+        let temp_file = write_config(&config);
+        let path = temp_file.path().to_str().unwrap();
+        let (_, settings) = file_configured_sources(path).unwrap();
+        assert_eq!(settings.art.sad.frames, vec!["(o_o)"]);
+
+        std::fs::remove_file(dir.path().join("sad.txt")).unwrap();
+        assert!(file_configured_sources(path).is_err());
+    }
+
+    #[test]
+    fn config_file_keys_are_parsed() {
+        let config = "{ \
+                \"keys\": { \"next\": [ \"n\", \"down\" ], \"quit\": \"ctrl-q\" }, \
+                \"git\": [ \"foo/bar/baz\" ] \
+            }";
+        let temp_file = write_config(config);
+        let path = temp_file.path().to_str().unwrap();
+        let (_, settings) = file_configured_sources(path).unwrap();
+        assert_eq!(settings.keys.keys_text(Action::Next), "n, ↓");
+        assert_eq!(settings.keys.keys_text(Action::Quit), "Ctrl-q");
+        assert_eq!(settings.keys.keys_text(Action::Previous), "↑, k");
+
+        for keys in [
+            "{ \"jump\": \"j\" }",
+            "{ \"next\": \"nope\" }",
+            "{ \"next\": \"x\", \"previous\": \"x\" }",
+        ] {
+            let config = format!("{{ \"keys\": {keys}, \"git\": [ \"foo/bar/baz\" ] }}");
+            let temp_file = write_config(&config);
+            let path = temp_file.path().to_str().unwrap();
+            assert!(file_configured_sources(path).is_err(), "{keys}");
+        }
+    }
+
+    #[test]
+    fn config_file_git_settings_are_parsed() {
+        let config = "{ \
+                \"git\": [ \
+                    \"foo/bar/baz\", \
+                    { \"path\": \"mi/mu/meh\", \"refs\": [ \"remote\", \"tags\" ], \"tag_time\": \"commit\", \"snacking\": true } \
+                ] \
+            }";
+        let temp_file = write_config(config);
+        let path = temp_file.path().to_str().unwrap();
+        let (sources, _) = file_configured_sources(path).unwrap();
+        let git: Vec<_> = sources
+            .iter()
+            .map(|source| match source {
+                Source::Git(source) => (source.refs.clone(), source.snacking),
+                _ => panic!("unexpected source"),
+            })
+            .collect();
+        assert_eq!(
+            git,
+            vec![
+                (GitRefs::default(), false),
+                (
+                    GitRefs {
+                        kinds: vec![RefKind::Remote, RefKind::Tags],
+                        tag_time: TagTime::Commit,
+                    },
+                    true
+                )
+            ]
+        );
+
+        for git in [
+            "{ \"path\": \"a\", \"refs\": [ \"stashes\" ] }",
+            "{ \"path\": \"a\", \"tag_time\": \"now\" }",
+            "{ \"path\": \"a\", \"snacking\": \"sometimes\" }",
+        ] {
+            let config = format!("{{ \"git\": [ {git} ] }}");
+            let temp_file = write_config(&config);
+            let path = temp_file.path().to_str().unwrap();
+            assert!(file_configured_sources(path).is_err(), "{git}");
+        }
+    }
+
+    #[test]
+    fn config_file_branches_are_parsed() {
+        let config = "{ \
+                \"branches\": { \"exclude\": [ \"dependabot/*\" ] }, \
+                \"git\": [ \
+                    \"foo/bar/baz\", \
+                    { \"path\": \"mi/mu/meh\", \"branches\": { \"include\": [ \"main\" ] } } \
+                ] \
+            }";
+        let temp_file = write_config(config);
+        let path = temp_file.path().to_str().unwrap();
+        let (sources, _) = file_configured_sources(path).unwrap();
+        assert_eq!(
+            sources[0].settings().branches,
+            RefFilter {
+                include: vec![],
+                exclude: vec!["dependabot/*".into()],
+            }
+        );
+        assert_eq!(
+            sources[1].settings().branches,
+            RefFilter {
+                include: vec!["main".into()],
+                exclude: vec![],
+            }
+        );
+
+        for branches in ["[ \"main\" ]", "{ \"only\": [ \"main\" ] }"] {
+            let config = format!("{{ \"branches\": {branches}, \"git\": [ \"foo/bar/baz\" ] }}");
+            let temp_file = write_config(&config);
+            let path = temp_file.path().to_str().unwrap();
+            assert!(file_configured_sources(path).is_err(), "{branches}");
+        }
+    }
+
+    #[test]
+    fn config_file_authors_are_parsed() {
+        let config = "{ \
+                \"authors\": [ \"dawe\", \"/@example\\\\.org$/\" ], \
+                \"git\": [ \
+                    \"foo/bar/baz\", \
+                    { \"path\": \"mi/mu/meh\", \"authors\": [] } \
+                ] \
+            }";
+        let temp_file = write_config(config);
+        let path = temp_file.path().to_str().unwrap();
+        let (sources, _) = file_configured_sources(path).unwrap();
+        assert_eq!(
+            sources[0].settings().authors.patterns,
+            vec![
+                "dawe".parse().unwrap(),
+                "/@example\\.org$/".parse().unwrap()
+            ]
+        );
+        assert_eq!(sources[1].settings().authors, AuthorFilter::default());
+
+        for authors in ["\"dawe\"", "[ \"/(/\" ]"] {
+            let config = format!("{{ \"authors\": {authors}, \"git\": [ \"foo/bar/baz\" ] }}");
+            let temp_file = write_config(&config);
+            let path = temp_file.path().to_str().unwrap();
+            assert!(file_configured_sources(path).is_err(), "{authors}");
+        }
+    }
+
+    #[test]
+    fn config_file_ignore_rules_are_parsed() {
+        let config = "{ \
+                \"ignore\": { \"merges\": true, \"bots\": [ \"/\\\\[bot\\\\]$/\" ], \"messages\": [ \"^fixup!\" ] }, \
+                \"git\": [ \
+                    \"foo/bar/baz\", \
+                    { \"path\": \"mi/mu/meh\", \"ignore\": { \"empty\": true } }, \
+                    { \"path\": \"la/le/lu\", \"ignore\": { \"merges\": false, \"messages\": [] } } \
+                ] \
+            }";
+        let temp_file = write_config(config);
+        let path = temp_file.path().to_str().unwrap();
+        let (sources, _) = file_configured_sources(path).unwrap();
+        assert_eq!(
+            sources[0].settings().ignore,
+            IgnoreRules {
+                merges: true,
+                empty: false,
+                bots: vec!["/\\[bot\\]$/".parse().unwrap()],
+                messages: vec![Regex::new("^fixup!").unwrap()],
+            }
+        );
+        // the rules of a source are merged with the global ones key by key
+        assert_eq!(
+            sources[1].settings().ignore,
+            IgnoreRules {
+                empty: true,
+                ..sources[0].settings().ignore.clone()
+            }
+        );
+        assert_eq!(
+            sources[2].settings().ignore,
+            IgnoreRules {
+                merges: false,
+                empty: false,
+                bots: vec!["/\\[bot\\]$/".parse().unwrap()],
+                messages: vec![],
+            }
+        );
+
+        for ignore in [
+            "{ \"merges\": \"sometimes\" }",
+            "{ \"messages\": [ \"(\" ] }",
+            "{ \"tags\": true }",
+        ] {
+            let config = format!("{{ \"ignore\": {ignore}, \"git\": [ \"foo/bar/baz\" ] }}");
+            let temp_file = write_config(&config);
+            let path = temp_file.path().to_str().unwrap();
+            assert!(file_configured_sources(path).is_err(), "{ignore}");
+        }
+    }
+
+    #[test]
+    fn config_file_intervals_are_parsed() {
+        let config = "{ \
+                \"interval\": 120, \
+                \"git\": [ \
+                    \"foo/bar/baz\", \
+                    { \"path\": \"mi/mu/meh\", \"interval\": 1.5 } \
+                ] \
+            }";
+        let temp_file = write_config(config);
+        let path = temp_file.path().to_str().unwrap();
+        let (sources, _) = file_configured_sources(path).unwrap();
+
+        assert_eq!(sources[0].settings().interval_secs, Some(120.0));
+        assert_eq!(sources[1].settings().interval_secs, Some(1.5));
+
+        let temp_file = write_config("{ \"interval\": 1e39, \"git\": [ \"foo/bar/baz\" ] }");
+        assert!(file_configured_sources(temp_file.path().to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn config_file_http_settings_are_parsed() {
+        let config = "{ \
+                \"http\": { \"connect_timeout\": 3, \"retries\": 5, \"retry_delay\": 0.25 }, \
+                \"git\": [ \"foo/bar/baz\" ] \
+            }";
+        let temp_file = write_config(config);
+        let path = temp_file.path().to_str().unwrap();
+        let (_, settings) = file_configured_sources(path).unwrap();
+
+        assert_eq!(settings.http.connect_timeout, Duration::from_secs(3));
+        assert_eq!(
+            settings.http.read_timeout,
+            HttpSettings::default().read_timeout
+        );
+        assert_eq!(settings.http.retries, 5);
+        assert_eq!(settings.http.retry_delay, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn config_file_connection_settings_are_global_and_per_source() {
+        let config = "{ \
+                \"proxy\": \"http://proxy.example.org:3128\", \
+                \"no_proxy\": [ \"localhost\" ], \
+                \"forgejo\": [ \
+                    { \"baseurl\": \"https://codeberg.org\", \"repo\": \"cb_owner1/cb_repo1\" }, \
+                    { \"baseurl\": \"https://git.example.org\", \"repo\": \"cb_owner2/cb_repo2\", \
+                      \"root_certificates\": [ \"/etc/ssl/internal-ca.pem\" ], \
+                      \"client_certificate\": \"/etc/ssl/me.pem\", \"client_key\": \"/etc/ssl/me.key\" } \
+                ] \
+            }";
+        let temp_file = write_config(config);
+        let path = temp_file.path().to_str().unwrap();
+        let (sources, _) = file_configured_sources(path).unwrap();
+
+        let first = &sources[0].settings().connection;
+        assert_eq!(
+            first.proxy.as_deref(),
+            Some("http://proxy.example.org:3128")
+        );
+        assert_eq!(first.no_proxy, vec!["localhost".to_string()]);
+        assert!(first.root_certificates.is_empty());
+        assert!(first.client_certificate.is_none());
+
+        let second = &sources[1].settings().connection;
+        assert_eq!(second.proxy, first.proxy);
+        assert_eq!(
+            second.root_certificates,
+            vec![PathBuf::from("/etc/ssl/internal-ca.pem")]
+        );
+        assert_eq!(
+            second.client_certificate,
+            Some(PathBuf::from("/etc/ssl/me.pem"))
+        );
+        assert_eq!(second.client_key, Some(PathBuf::from("/etc/ssl/me.key")));
+    }
+
+    #[test]
+    fn config_file_with_client_certificate_without_key_should_err() {
+        let config = "{ \
+                \"client_certificate\": \"/etc/ssl/me.pem\", \
+                \"git\": [ \"foo/bar/baz\" ] \
+            }";
+        let temp_file = write_config(config);
+        let path = temp_file.path().to_str().unwrap();
+        assert!(file_configured_sources(path).is_err());
+
+        let config = "{ \
+                \"client_certificate\": \"/etc/ssl/me.pem\", \
+                \"client_key\": \"/etc/ssl/me.key\", \
+                \"forgejo\": [ \
+                    { \"baseurl\": \"https://codeberg.org\", \"repo\": \"cb_owner1/cb_repo1\", \
+                      \"client_certificate\": \"/etc/ssl/other.pem\" } \
+                ] \
+            }";
+        let temp_file = write_config(config);
+        let path = temp_file.path().to_str().unwrap();
+        assert!(file_configured_sources(path).is_err());
+    }
 }
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Datelike, NaiveDate, TimeDelta, TimeZone, Utc};
+
+use crate::activity::Activity;
+
+/// How many weeks of activity the rhythm of a source covers.
+pub const RHYTHM_WEEKS: usize = 26;
+
+/// The oldest time the rhythm of a source covers.
+pub fn rhythm_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    now - TimeDelta::weeks(RHYTHM_WEEKS as i64)
+}
+
+/// The number of activities on each of the `days` days up to `today` in the given time zone,
+/// oldest first.
+pub fn daily_counts<Tz: TimeZone>(
+    activities: &[Activity],
+    tz: &Tz,
+    today: NaiveDate,
+    days: usize,
+) -> Vec<u64> {
+    let mut counts = vec![0; days];
+    for activity in activities {
+        let date = activity.timestamp.with_timezone(tz).date_naive();
+        if let Ok(age) = usize::try_from((today - date).num_days())
+            && age < days
+        {
+            counts[days - 1 - age] += 1;
+        }
+    }
+    counts
+}
+
+/// The daily counts of the `weeks` weeks up to the one of `today` as calendar rows from Monday
+/// to Sunday, with `None` for the days after today.
+pub fn calendar<Tz: TimeZone>(
+    activities: &[Activity],
+    tz: &Tz,
+    today: NaiveDate,
+    weeks: usize,
+) -> [Vec<Option<u64>>; 7] {
+    let mut rows: [Vec<Option<u64>>; 7] = Default::default();
+    if weeks == 0 {
+        return rows;
+    }
+    let weekday = today.weekday().num_days_from_monday() as usize;
+    // starts on the Monday of the first week
+    let days = (weeks - 1) * 7 + weekday + 1;
+    for (day, count) in daily_counts(activities, tz, today, days)
+        .into_iter()
+        .enumerate()
+    {
+        rows[day % 7].push(Some(count));
+    }
+    for row in rows.iter_mut().skip(weekday + 1) {
+        row.push(None);
+    }
+    rows
+}
+
+/// How busy a day with `count` activities was on a scale from 0 to 4, relative to the busiest
+/// day with `max` activities.
+pub fn heat_level(count: u64, max: u64) -> usize {
+    if count == 0 || max == 0 {
+        0
+    } else {
+        (count * 4).div_ceil(max).min(4) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::ActivityKind;
+
+    fn commit_on(year: i32, month: u32, day: u32) -> Activity {
+        Activity {
+            timestamp: Utc.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap(),
+            kind: ActivityKind::Commit,
+            actor: None,
+            email: None,
+            git_ref: None,
+            message: None,
+        }
+    }
+
+    #[test]
+    fn activities_are_counted_per_day() {
+        let today = NaiveDate::from_ymd_opt(2025, 10, 16).unwrap();
+        let activities = [
+            commit_on(2025, 10, 16),
+            commit_on(2025, 10, 16),
+            commit_on(2025, 10, 14),
+            commit_on(2025, 9, 1),
+            commit_on(2025, 10, 17),
+        ];
+        assert_eq!(daily_counts(&activities, &Utc, today, 3), vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn calendar_rows_run_from_monday_to_sunday() {
+        // a Thursday
+        let today = NaiveDate::from_ymd_opt(2025, 10, 16).unwrap();
+        let activities = [commit_on(2025, 10, 6), commit_on(2025, 10, 16)];
+        let rows = calendar(&activities, &Utc, today, 2);
+
+        assert_eq!(rows[0], vec![Some(1), Some(0)]);
+        assert_eq!(rows[3], vec![Some(0), Some(1)]);
+        assert_eq!(rows[4], vec![Some(0), None]);
+        assert_eq!(rows[6], vec![Some(0), None]);
+    }
+
+    #[test]
+    fn heat_level_is_relative_to_busiest_day() {
+        assert_eq!(heat_level(0, 10), 0);
+        assert_eq!(heat_level(1, 10), 1);
+        assert_eq!(heat_level(5, 10), 2);
+        assert_eq!(heat_level(10, 10), 4);
+    }
+}
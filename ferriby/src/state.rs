@@ -0,0 +1,127 @@
+use std::{
+    collections::HashMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ActivityError;
+
+/// The directory ferriby keeps its state in: `$XDG_STATE_HOME/ferriby`, defaulting to
+/// `$HOME/.local/state/ferriby` (`$HOME/AppData/Local/ferriby` on Windows).
+pub fn state_dir() -> Option<PathBuf> {
+    let mut path = match env::var_os("XDG_STATE_HOME").map(PathBuf::from) {
+        Some(state_home) if state_home.is_absolute() => state_home,
+        _ => {
+            let mut home = env::home_dir()?;
+            if env::consts::OS == "windows" {
+                home.push("AppData");
+                home.push("Local");
+            } else {
+                home.push(".local");
+                home.push("state");
+            }
+            home
+        }
+    };
+    path.push("ferriby");
+    Some(path)
+}
+
+/// What is remembered of a source across runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredState {
+    /// The last activity seen in the source.
+    pub last_activity: Option<DateTime<Utc>>,
+    /// When the source was checked successfully the last time.
+    pub last_checked: Option<DateTime<Utc>>,
+    /// The error of the last check, if it failed.
+    pub error: Option<ActivityError>,
+}
+
+/// The file the states of the sources are persisted in, keyed by the names of the sources.
+#[derive(Debug, Clone)]
+pub struct StateFile {
+    path: PathBuf,
+}
+
+impl StateFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// The state file in the [`state_dir`].
+    pub fn default_path() -> Option<PathBuf> {
+        state_dir().map(|dir| dir.join("state.json"))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Loads the stored states, treating a missing or broken file as empty.
+    pub fn load(&self) -> HashMap<String, StoredState> {
+        fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Stores the given states, keeping the ones of sources not given.
+    pub fn save(&self, states: HashMap<String, StoredState>) -> io::Result<()> {
+        let mut stored = self.load();
+        stored.extend(states);
+        let content = serde_json::to_string_pretty(&stored)?;
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // write to a temporary file first to not leave a half written state behind
+        let tmp_path = self.path.with_extension("json.tmp");
+        fs::write(&tmp_path, content)?;
+        fs::rename(tmp_path, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_states_are_loaded_and_merged() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_file = StateFile::new(dir.path().join("nested").join("state.json"));
+        assert!(state_file.load().is_empty());
+
+        let first = StoredState {
+            last_activity: DateTime::from_timestamp(1_750_000_000, 0),
+            last_checked: DateTime::from_timestamp(1_760_000_000, 0),
+            error: None,
+        };
+        let second = StoredState {
+            last_activity: None,
+            last_checked: None,
+            error: Some(ActivityError::RateLimited),
+        };
+        state_file
+            .save(HashMap::from([("git: a".to_string(), first.clone())]))
+            .unwrap();
+        state_file
+            .save(HashMap::from([("github: b/c".to_string(), second.clone())]))
+            .unwrap();
+
+        let loaded = state_file.load();
+        assert_eq!(loaded.get("git: a"), Some(&first));
+        assert_eq!(loaded.get("github: b/c"), Some(&second));
+    }
+
+    #[test]
+    fn broken_state_file_is_empty() {
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), "{ not json").unwrap();
+        let state_file = StateFile::new(temp_file.path().to_path_buf());
+        assert!(state_file.load().is_empty());
+    }
+}
@@ -0,0 +1,233 @@
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::app::Happiness;
+
+/// The names of the built-in themes, in the order they are cycled through.
+pub const THEME_NAMES: [&str; 5] = ["dark", "light", "solarized", "high-contrast", "monochrome"];
+
+/// The styles of the different parts of the UI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// The name to select the theme by.
+    pub name: String,
+    /// Text and background of everything not styled otherwise.
+    pub base: Style,
+    /// The borders and titles of the panes.
+    pub border: Style,
+    /// The selected source in the list.
+    pub highlight: Style,
+    /// Secondary information like times and stale sources.
+    pub dimmed: Style,
+    /// Errors and warnings.
+    pub error: Style,
+    /// Branches and authors in the detail pane.
+    pub accent: Style,
+    /// The moods of the ferrises.
+    pub undecided: Style,
+    pub sad: Style,
+    pub okayish: Style,
+    pub buzzing: Style,
+    /// The days in the calendar, from idle to busiest.
+    pub heat: [Style; 5],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    /// The built-in theme with the given name.
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "solarized" => Some(Self::solarized()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "monochrome" => Some(Self::monochrome()),
+            _ => None,
+        }
+    }
+
+    /// All built-in themes.
+    pub fn builtins() -> Vec<Self> {
+        THEME_NAMES
+            .iter()
+            .filter_map(|name| Self::by_name(name))
+            .collect()
+    }
+
+    /// The style of the given role, as named in the config file.
+    pub fn role_mut(&mut self, role: &str) -> Option<&mut Style> {
+        match role {
+            "base" => Some(&mut self.base),
+            "border" => Some(&mut self.border),
+            "highlight" => Some(&mut self.highlight),
+            "dimmed" => Some(&mut self.dimmed),
+            "error" => Some(&mut self.error),
+            "accent" => Some(&mut self.accent),
+            "undecided" => Some(&mut self.undecided),
+            "sad" => Some(&mut self.sad),
+            "okayish" => Some(&mut self.okayish),
+            "buzzing" => Some(&mut self.buzzing),
+            _ => None,
+        }
+    }
+
+    /// The style of a ferris in the given mood.
+    pub fn happiness(&self, happiness: Happiness) -> Style {
+        match happiness {
+            Happiness::Undecided => self.undecided,
+            Happiness::Sad => self.sad,
+            Happiness::Okayish => self.okayish,
+            Happiness::Buzzing => self.buzzing,
+        }
+    }
+
+    fn dark() -> Self {
+        let fg = |color| Style::new().fg(color);
+        Self {
+            name: "dark".into(),
+            base: Style::new().fg(Color::Cyan).bg(Color::Black),
+            border: fg(Color::Cyan),
+            highlight: Style::new().add_modifier(Modifier::BOLD),
+            dimmed: fg(Color::DarkGray),
+            error: fg(Color::Red),
+            accent: fg(Color::Yellow),
+            undecided: fg(Color::Gray),
+            sad: fg(Color::Red),
+            okayish: fg(Color::Yellow),
+            buzzing: fg(Color::Green),
+            heat: [
+                fg(Color::DarkGray),
+                fg(Color::Indexed(22)),
+                fg(Color::Indexed(28)),
+                fg(Color::Indexed(34)),
+                fg(Color::Indexed(40)),
+            ],
+        }
+    }
+
+    fn light() -> Self {
+        let fg = |color| Style::new().fg(color);
+        Self {
+            name: "light".into(),
+            base: Style::new().fg(Color::Black).bg(Color::White),
+            border: fg(Color::Blue),
+            highlight: Style::new().fg(Color::White).bg(Color::Blue),
+            dimmed: fg(Color::Indexed(244)),
+            error: fg(Color::Red),
+            accent: fg(Color::Magenta),
+            undecided: fg(Color::Indexed(244)),
+            sad: fg(Color::Red),
+            okayish: fg(Color::Indexed(136)),
+            buzzing: fg(Color::Indexed(28)),
+            heat: [
+                fg(Color::Rgb(235, 237, 240)),
+                fg(Color::Rgb(155, 233, 168)),
+                fg(Color::Rgb(64, 196, 99)),
+                fg(Color::Rgb(48, 161, 78)),
+                fg(Color::Rgb(33, 110, 57)),
+            ],
+        }
+    }
+
+    fn solarized() -> Self {
+        let fg = |color| Style::new().fg(color);
+        let base01 = Color::Rgb(88, 110, 117);
+        let red = Color::Rgb(220, 50, 47);
+        let yellow = Color::Rgb(181, 137, 0);
+        let green = Color::Rgb(133, 153, 0);
+        Self {
+            name: "solarized".into(),
+            base: Style::new()
+                .fg(Color::Rgb(131, 148, 150))
+                .bg(Color::Rgb(0, 43, 54)),
+            border: fg(Color::Rgb(38, 139, 210)),
+            highlight: Style::new()
+                .bg(Color::Rgb(7, 54, 66))
+                .add_modifier(Modifier::BOLD),
+            dimmed: fg(base01),
+            error: fg(red),
+            accent: fg(yellow),
+            undecided: fg(base01),
+            sad: fg(red),
+            okayish: fg(yellow),
+            buzzing: fg(green),
+            heat: [
+                fg(Color::Rgb(7, 54, 66)),
+                fg(Color::Rgb(60, 80, 20)),
+                fg(Color::Rgb(90, 110, 10)),
+                fg(Color::Rgb(115, 135, 5)),
+                fg(green),
+            ],
+        }
+    }
+
+    fn high_contrast() -> Self {
+        let bold = |color| Style::new().fg(color).add_modifier(Modifier::BOLD);
+        Self {
+            name: "high-contrast".into(),
+            base: Style::new().fg(Color::White).bg(Color::Black),
+            border: bold(Color::White),
+            highlight: Style::new()
+                .fg(Color::Black)
+                .bg(Color::White)
+                .add_modifier(Modifier::BOLD),
+            dimmed: Style::new().fg(Color::White),
+            error: bold(Color::LightRed),
+            accent: bold(Color::LightYellow),
+            undecided: bold(Color::White),
+            sad: bold(Color::LightRed),
+            okayish: bold(Color::LightYellow),
+            buzzing: bold(Color::LightGreen),
+            heat: [
+                Style::new().fg(Color::White),
+                bold(Color::Indexed(28)),
+                bold(Color::Indexed(34)),
+                bold(Color::Indexed(40)),
+                bold(Color::Indexed(46)),
+            ],
+        }
+    }
+
+    fn monochrome() -> Self {
+        let with = |modifier| Style::new().add_modifier(modifier);
+        Self {
+            name: "monochrome".into(),
+            base: Style::new(),
+            border: Style::new(),
+            highlight: with(Modifier::REVERSED),
+            dimmed: with(Modifier::DIM),
+            error: with(Modifier::BOLD | Modifier::UNDERLINED),
+            accent: with(Modifier::ITALIC),
+            undecided: with(Modifier::DIM),
+            sad: with(Modifier::BOLD),
+            okayish: Style::new(),
+            buzzing: with(Modifier::BOLD),
+            heat: [
+                with(Modifier::DIM),
+                Style::new(),
+                Style::new(),
+                with(Modifier::BOLD),
+                with(Modifier::BOLD),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_themes_are_found_by_name() {
+        let themes = Theme::builtins();
+        assert_eq!(themes.len(), THEME_NAMES.len());
+        for (theme, name) in themes.iter().zip(THEME_NAMES) {
+            assert_eq!(theme.name, name);
+        }
+        assert_eq!(Theme::by_name("neon"), None);
+    }
+}
@@ -1,178 +1,740 @@
-use crate::app::{App, Happiness};
+use crate::{
+    activity::{Activity, ActivityKind},
+    app::{App, Happiness, SourceState},
+    art::{MAX_FRAME_HEIGHT, MAX_FRAME_WIDTH, frame_size},
+    githoster::RateLimit,
+    history::HungrySpell,
+    keys::Action,
+    rhythm::{RHYTHM_WEEKS, calendar, daily_counts, heat_level},
+    theme::Theme,
+};
+use chrono::{DateTime, Local, TimeDelta, Utc};
 use ratatui::{
     buffer::Buffer,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
-    widgets::{Block, BorderType, List, ListItem, ListState, Paragraph, StatefulWidget, Widget},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Position, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        Block, BorderType, Clear, List, ListItem, ListState, Padding, Paragraph, Sparkline,
+        StatefulWidget, Widget,
+    },
 };
 
-fn ferris(happiness: Happiness, animation: usize) -> String {
-    let undecided_ferris = {
-        let ferrises = [
-            r"
-    _~^~^~_        
-   / o  o  \       
-  '_       _'      
-  \ '-----' /      
-",
-            r"
-    _~^~^~_       
-   /  o  o \      
-  '_       _'     
-  \ '-----' /     
-",
-        ];
-        ferrises[animation % ferrises.len()]
-    };
+/// The lines of a frame of the ferris, padded to the same width to stay aligned when centered.
+fn frame_lines(frame: &str, style: Style) -> Vec<Line<'static>> {
+    let lines: Vec<&str> = frame.lines().map(str::trim_end).collect();
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| Line::styled(format!("{line:<width$}"), style))
+        .collect()
+}
 
-    let sad_ferris = {
-        let ferrises = [
-            r"
-    _~^~^~_       
-\) / .  .  \ (/   
-  '_  / \  _'     
-  \ '-----' \     
-",
-            r"
-    _~^~^~_       
-\) /  .  . \ (/   
-  '_  / \  _'     
-  / '-----' /     
-",
-        ];
-        ferrises[animation % ferrises.len()]
-    };
+/// The glyph showing the happiness in the sources list.
+fn mood_glyph(happiness: Happiness) -> &'static str {
+    match happiness {
+        Happiness::Undecided => "·",
+        Happiness::Sad => "▼",
+        Happiness::Okayish => "■",
+        Happiness::Buzzing => "▲",
+    }
+}
 
-    let okayish_ferris = {
-        let ferrises = [
-            r"
-    _~^~^~_       
-\) /  o o  \ (/   
-  '_   ==  _'     
-  \ '-----' /     
-",
-            r"
-    _~^~^~_       
-\) /  o o  \ (/   
-  '_  ==   _'     
-  \ '-----' /     
-",
-        ];
+/// The glyphs of the days in the calendar, from idle to busiest, readable even without colours.
+const HEAT_GLYPHS: [&str; 5] = ["· ", "░ ", "▒ ", "▓ ", "█ "];
+
+/// The smallest size of a tile in the grid that still fits a ferris, its borders and a line.
+const TILE_WIDTH: u16 = MAX_FRAME_WIDTH as u16 + 2;
+const TILE_HEIGHT: u16 = MAX_FRAME_HEIGHT as u16 + 3;
 
-        ferrises[animation % ferrises.len()]
+/// How many columns and rows of tiles fit `tiles` tiles into the given area with the biggest
+/// tiles, measured in multiples of the smallest tile.
+fn grid_dimensions(tiles: usize, width: u16, height: u16) -> (usize, usize) {
+    let scale = |cols: usize| {
+        let rows = tiles.div_ceil(cols);
+        let tile_width = f32::from(width) / cols as f32 / f32::from(TILE_WIDTH);
+        let tile_height = f32::from(height) / rows as f32 / f32::from(TILE_HEIGHT);
+        tile_width.min(tile_height)
     };
+    // on a tie, the fewest columns win as they look the least squeezed
+    let cols = (1..=tiles.max(1))
+        .rev()
+        .max_by(|a, b| scale(*a).total_cmp(&scale(*b)))
+        .unwrap_or(1);
+    (cols, tiles.div_ceil(cols))
+}
 
-    let buzzing_ferris = {
-        let ferrises = [
-            r"
-    _~^~^~_       
-\/ /  o O  \ \/   
-  '_  \_/  _'     
-  \ '-----' /     
-",
-            r"
-\/  _~^^^~_  \/   
- \ /  O o  \ /    
-  '_  *o*  _'     
-  / '-----' \     
-",
-            r"
-    _~^~^~_       
-\/ /  o O  \ \/   
-  '_  \_/  _'     
-  \ '-----' /     
-",
-            r"
-    _~^~^~_       
-\  /  O -  \  /   
-  '_  \_/  _'     
-  \ '-----' /     
-",
-        ];
+/// The areas of the tiles of `tiles` sources and of the help line in the grid view.
+fn grid_layout(area: Rect, tiles: usize) -> (Vec<Rect>, Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(area.inner(Margin::new(2, 2)));
+    let (cols, rows) = grid_dimensions(tiles, chunks[0].width, chunks[0].height);
+    let row_areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Ratio(1, rows as u32); rows])
+        .split(chunks[0]);
+    let tile_areas = row_areas
+        .iter()
+        .flat_map(|row_area| {
+            Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![Constraint::Ratio(1, cols as u32); cols])
+                .split(*row_area)
+                .to_vec()
+        })
+        .collect();
+    (tile_areas, chunks[1])
+}
+
+/// How many of the most recent activities the detail pane lists.
+const RECENT_ACTIVITIES: usize = 5;
+
+/// The areas of the sources list, the main pane and the recent activities in the list view.
+fn list_view_layout(area: Rect) -> (Rect, Rect, Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .margin(2)
+        .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)])
+        .split(area);
+    let main_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(RECENT_ACTIVITIES as u16 + 2),
+        ])
+        .split(chunks[1]);
+    (chunks[0], main_chunks[0], main_chunks[1])
+}
+
+/// How many lines about the source are shown above the ferris in the main pane.
+const INFO_LINES: u16 = 4;
+
+/// The areas of the source with its ferris, the rhythm and the help line in the main pane.
+fn main_layout(area: Rect) -> (Rect, Rect, Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Max(1),
+            Constraint::Min(3),
+            Constraint::Length(10),
+            Constraint::Max(1),
+            Constraint::Max(1),
+        ])
+        .split(area);
+    (chunks[1], chunks[2].inner(Margin::new(2, 0)), chunks[3])
+}
+
+/// A short human readable form of a duration in its largest unit.
+fn short_duration(delta: TimeDelta) -> String {
+    match delta {
+        _ if delta < TimeDelta::minutes(1) => format!("{}s", delta.num_seconds().max(0)),
+        _ if delta < TimeDelta::hours(1) => format!("{}m", delta.num_minutes()),
+        _ if delta < TimeDelta::days(1) => format!("{}h", delta.num_hours()),
+        _ if delta < TimeDelta::weeks(1) => format!("{}d", delta.num_days()),
+        _ => format!("{}w", delta.num_weeks()),
+    }
+}
+
+/// A short human readable form of how long ago something happened.
+fn relative_time(delta: TimeDelta) -> String {
+    if delta < TimeDelta::minutes(1) {
+        "just now".into()
+    } else {
+        format!("{} ago", short_duration(delta))
+    }
+}
+
+/// When and for how long the ferris of a source went hungry the last time.
+fn hungry_text(spell: Option<HungrySpell>, now: DateTime<Utc>) -> String {
+    match spell {
+        Some(spell @ HungrySpell { end: None, .. }) => {
+            format!(" (hungry for {})", short_duration(spell.duration(now)))
+        }
+        Some(spell @ HungrySpell { end: Some(end), .. }) => format!(
+            " (last hungry for {}, {})",
+            short_duration(spell.duration(now)),
+            relative_time(now - end)
+        ),
+        None => String::new(),
+    }
+}
 
-        ferrises[animation % ferrises.len()]
+/// A line of the detail pane describing what happened when, where and by whom.
+fn activity_line(activity: &Activity, now: DateTime<Utc>, theme: &Theme) -> Line<'static> {
+    let what = match (&activity.message, &activity.kind) {
+        (Some(message), _) => message.clone(),
+        (None, ActivityKind::Commit) => "committed".into(),
+        (None, ActivityKind::Push) => "pushed".into(),
+        (None, ActivityKind::Merge) => "merged".into(),
+        (None, ActivityKind::Create) => "created".into(),
+        (None, ActivityKind::Delete) => "deleted".into(),
+        (None, ActivityKind::Note) => "noted".into(),
+        (None, ActivityKind::Snack) => "changed uncommitted files".into(),
+        (None, ActivityKind::Other(name)) => name.clone(),
     };
+    let mut spans = vec![Span::styled(
+        format!("{:>9} ", relative_time(now - activity.timestamp)),
+        theme.dimmed,
+    )];
+    if let Some(git_ref) = &activity.git_ref {
+        spans.push(Span::styled(
+            format!("[{}] ", Activity::short_ref(git_ref)),
+            theme.accent,
+        ));
+    }
+    if let Some(actor) = &activity.actor {
+        spans.push(Span::styled(
+            format!("{actor}: "),
+            theme.accent.add_modifier(Modifier::BOLD),
+        ));
+    }
+    spans.push(Span::raw(what));
+    Line::from(spans)
+}
 
-    match happiness {
-        Happiness::Undecided => undecided_ferris.into(),
-        Happiness::Sad => sad_ferris.into(),
-        Happiness::Okayish => okayish_ferris.into(),
-        Happiness::Buzzing => buzzing_ferris.into(),
+/// The remaining request budget of a git hoster.
+fn rate_limit_text(rate_limit: &RateLimit) -> String {
+    let now = Utc::now();
+    if let Some(until) = rate_limit
+        .paused_until
+        .filter(|_| rate_limit.is_paused(now))
+    {
+        return format!(
+            "API rate limited, pausing for {}",
+            short_duration(until - now)
+        );
+    }
+    let budget = match (rate_limit.remaining, rate_limit.limit) {
+        (Some(remaining), Some(limit)) => format!("API budget: {remaining}/{limit}"),
+        (Some(remaining), None) => format!("API budget: {remaining}"),
+        _ => return String::new(),
+    };
+    match rate_limit.reset.filter(|reset| *reset > now) {
+        Some(reset) => format!("{budget}, resets in {}", short_duration(reset - now)),
+        None => budget,
     }
 }
 
 impl App {
-    fn get_style() -> Style {
-        Style::default().fg(Color::Cyan).bg(Color::Black)
+    /// The mood the ferris of the source with the given index is shown in, buzzing for a
+    /// moment after it was clicked.
+    fn ferris_mood(&self, index: usize) -> Happiness {
+        if index == self.selected && self.reaction > 0 {
+            Happiness::Buzzing
+        } else {
+            self.states[index].happiness
+        }
+    }
+
+    /// The current frame of the ferris of the source with the given index.
+    fn ferris_lines(&self, index: usize) -> Vec<Line<'static>> {
+        let happiness = self.ferris_mood(index);
+        let style = match self.art.mood(happiness).color {
+            Some(color) => Style::new().fg(color),
+            None => Style::new(),
+        };
+        frame_lines(self.art.frame(happiness, self.animation), style)
+    }
+
+    fn list_line(source: String, state: &SourceState, stale: bool, theme: &Theme) -> Line<'static> {
+        let name_style = if stale {
+            theme.dimmed
+        } else {
+            Style::default()
+        };
+        let mut spans = vec![
+            Span::styled(
+                mood_glyph(state.happiness),
+                theme.happiness(state.happiness),
+            ),
+            Span::styled(format!(" {source}"), name_style),
+        ];
+        if let Some(last_activity) = state.last_activity {
+            let fed = relative_time(Utc::now() - last_activity);
+            let note = if stale {
+                format!(" ({fed}, stale)")
+            } else {
+                format!(" ({fed})")
+            };
+            spans.push(Span::styled(note, theme.dimmed));
+        } else if stale {
+            spans.push(Span::styled(" (stale)", theme.dimmed));
+        }
+        if state.error.is_some() {
+            spans.push(Span::styled(" ⚠", theme.error));
+        }
+        Line::from(spans)
     }
 
     fn render_list(&self, area: Rect, buf: &mut Buffer) {
+        let theme = self.theme();
         let block = Block::bordered()
             .title(" Sources ")
             .title_alignment(Alignment::Center)
-            .border_type(BorderType::Rounded);
+            .border_type(BorderType::Rounded)
+            .border_style(theme.border);
 
-        let items = self.sources.iter().map(|source| {
-            let s: String = format!("{source}");
-            ListItem::new(s)
-        });
+        let now = Utc::now();
+        let items = self
+            .sources
+            .iter()
+            .zip(self.states.iter())
+            .map(|(source, state)| {
+                let stale = state.is_stale(source.interval_secs(), now);
+                ListItem::new(App::list_line(format!("{source}"), state, stale, theme))
+            });
 
         let list = List::new(items)
             .block(block)
-            .style(App::get_style())
+            .style(theme.base)
+            .highlight_style(theme.highlight)
             .highlight_symbol(">> ");
         let mut list_state = ListState::default().with_selected(Some(self.selected));
         StatefulWidget::render(list, area, buf, &mut list_state);
     }
     fn render_main(&self, area: Rect, buf: &mut Buffer) {
-        let happiness: String = self.happiness.into();
-        let ferris = ferris(self.happiness, self.animation);
-        let text = format!(
-            "{}\n\
-             Happiness level: {}\n\
-             {}",
-            self.sources[self.selected], happiness, ferris
+        let happiness: String = self.happiness().into();
+        let theme = self.theme();
+        let (Some(state), Some(source)) = (
+            self.states.get(self.selected),
+            self.sources.get(self.selected),
+        ) else {
+            return;
+        };
+        let now = Utc::now();
+        let mut status = match &state.error {
+            Some(e) => format!("Error: {e}"),
+            None => String::new(),
+        };
+        if let Some(last_checked) = state
+            .last_checked
+            .filter(|_| state.is_stale(source.interval_secs(), now))
+        {
+            if !status.is_empty() {
+                status.push_str(", ");
+            }
+            status.push_str(&format!(
+                "stale, last checked {}",
+                relative_time(now - last_checked)
+            ));
+        }
+        let rate_limit = self
+            .rate_limit()
+            .map(|rate_limit| rate_limit_text(&rate_limit))
+            .unwrap_or_default();
+        let hungry = hungry_text(
+            state
+                .history
+                .last_hungry_spell(source.settings().thresholds.okayish(), now),
+            now,
         );
+        let snacking = state
+            .snacking
+            .map(|snacking| {
+                format!(
+                    ", snacking on uncommitted changes from {}",
+                    relative_time(now - snacking)
+                )
+            })
+            .unwrap_or_default();
+        let mut lines = vec![
+            Line::from(source.to_string()),
+            Line::from(format!("Happiness level: {happiness}{hungry}{snacking}")),
+            Line::from(status),
+            Line::from(rate_limit),
+        ];
+        lines.extend(self.ferris_lines(self.selected));
 
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Max(1),
-                Constraint::Min(3),
-                Constraint::Max(1),
-                Constraint::Max(1),
-            ])
-            .split(area);
-        let top_area = chunks[1];
-        let help_area = chunks[2];
+        let (top_area, rhythm_area, help_area) = main_layout(area);
 
         Block::bordered()
             .title(" Ferriby ")
-            .style(App::get_style())
+            .style(theme.base)
             .title_alignment(Alignment::Center)
             .border_type(BorderType::Rounded)
+            .border_style(theme.border)
             .render(area, buf);
 
-        Paragraph::new(text)
-            .style(App::get_style())
+        Paragraph::new(lines)
+            .style(theme.base)
             .centered()
             .render(top_area, buf);
-        Paragraph::new("Exit: q, Previous/Next Source: ↑/↓")
-            .style(App::get_style())
+        self.render_rhythm(rhythm_area, buf);
+        if self.kiosk.is_none() {
+            Paragraph::new(self.help_hint())
+                .style(theme.base)
+                .centered()
+                .render(help_area, buf);
+        }
+    }
+
+    /// Renders every source in its own tile with its ferris.
+    fn render_grid(&self, area: Rect, buf: &mut Buffer) {
+        let theme = self.theme();
+        Block::new()
+            .style(theme.base)
+            .render(area.inner(Margin::new(2, 2)), buf);
+        let (tile_areas, help_area) = grid_layout(area, self.sources.len());
+
+        let now = Utc::now();
+        for (index, ((source, state), tile_area)) in self
+            .sources
+            .iter()
+            .zip(&self.states)
+            .zip(tile_areas)
+            .enumerate()
+        {
+            let border_type = if index == self.selected {
+                BorderType::Thick
+            } else {
+                BorderType::Rounded
+            };
+            let block = Block::bordered()
+                .title(format!(" {source} "))
+                .title_alignment(Alignment::Center)
+                .border_type(border_type)
+                .border_style(theme.happiness(state.happiness));
+            let fed = match state.last_activity {
+                Some(last_activity) => format!("fed {}", relative_time(now - last_activity)),
+                None => "not fed yet".into(),
+            };
+            let mut lines = self.ferris_lines(index);
+            lines.push(Line::from(fed));
+            Paragraph::new(lines)
+                .block(block)
+                .style(theme.base)
+                .centered()
+                .render(tile_area, buf);
+        }
+
+        if self.kiosk.is_none() {
+            Paragraph::new(self.help_hint())
+                .style(theme.base)
+                .centered()
+                .render(help_area, buf);
+        }
+    }
+
+    /// The line hinting at the keys to exit and to show the help.
+    fn help_hint(&self) -> String {
+        let first_key = |action| {
+            self.keys
+                .keys(action)
+                .first()
+                .map(ToString::to_string)
+                .unwrap_or_else(|| "-".into())
+        };
+        format!(
+            "Exit: {}, Help: {}",
+            first_key(Action::Quit),
+            first_key(Action::Help)
+        )
+    }
+
+    /// Renders the help listing the keys of all actions above everything else.
+    fn render_help(&self, area: Rect, buf: &mut Buffer) {
+        let theme = self.theme();
+        let rows: Vec<(String, &str)> = Action::ALL
+            .iter()
+            .map(|action| (self.keys.keys_text(*action), action.description()))
+            .collect();
+        let keys_width = rows
+            .iter()
+            .map(|(keys, _)| keys.chars().count())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<Line> = rows
+            .into_iter()
+            .map(|(keys, description)| {
+                Line::from(vec![
+                    Span::styled(format!("{keys:>keys_width$}  "), theme.accent),
+                    Span::raw(description),
+                ])
+            })
+            .collect();
+
+        let width = lines
+            .iter()
+            .map(|line| line.width() as u16 + 4)
+            .max()
+            .unwrap_or(0)
+            .min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        let block = Block::bordered()
+            .title(" Keys ")
+            .title_alignment(Alignment::Center)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.border)
+            .padding(Padding::horizontal(1));
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .block(block)
+            .style(theme.base)
+            .render(popup, buf);
+    }
+
+    /// Renders the most recent activities of the selected source.
+    fn render_recent(&self, area: Rect, buf: &mut Buffer) {
+        let theme = self.theme();
+        let block = Block::bordered()
+            .title(" Recent activity ")
+            .title_alignment(Alignment::Center)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.border);
+        let now = Utc::now();
+        let lines: Vec<Line> = match self.states.get(self.selected) {
+            Some(state) if !state.history.activities().is_empty() => state
+                .history
+                .activities()
+                .iter()
+                .rev()
+                .take(RECENT_ACTIVITIES)
+                .map(|activity| activity_line(activity, now, theme))
+                .collect(),
+            _ => vec![Line::from("No activity seen yet")],
+        };
+        Paragraph::new(lines)
+            .block(block)
+            .style(theme.base)
+            .render(area, buf);
+    }
+
+    /// Renders the activity per day of the selected source as sparkline and calendar.
+    fn render_rhythm(&self, area: Rect, buf: &mut Buffer) {
+        let Some(state) = self.states.get(self.selected) else {
+            return;
+        };
+        let theme = self.theme();
+        let activities = state.history.activities();
+        let today = Local::now().date_naive();
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(2),
+                Constraint::Length(7),
+            ])
+            .split(area);
+
+        Paragraph::new(format!("Rhythm of the last {RHYTHM_WEEKS} weeks"))
+            .style(theme.base)
+            .centered()
+            .render(chunks[0], buf);
+
+        let days = (chunks[1].width as usize).min(RHYTHM_WEEKS * 7);
+        let counts = daily_counts(activities, &Local, today, days);
+        Sparkline::default()
+            .data(&counts)
+            .style(theme.base.patch(theme.buzzing))
+            .render(chunks[1], buf);
+
+        let weeks = (chunks[2].width as usize / 2).min(RHYTHM_WEEKS);
+        let rows = calendar(activities, &Local, today, weeks);
+        let busiest = rows.iter().flatten().flatten().copied().max().unwrap_or(0);
+        let lines: Vec<Line> = rows
+            .iter()
+            .map(|row| {
+                let days: Vec<Span> = row
+                    .iter()
+                    .map(|day| match day {
+                        Some(count) => {
+                            let level = heat_level(*count, busiest);
+                            Span::styled(HEAT_GLYPHS[level], theme.heat[level])
+                        }
+                        None => Span::raw("  "),
+                    })
+                    .collect();
+                Line::from(days)
+            })
+            .collect();
+        Paragraph::new(lines)
+            .style(theme.base)
             .centered()
-            .render(help_area, buf);
+            .render(chunks[2], buf);
+    }
+
+    /// Renders the list of sources next to the details of the selected one.
+    fn render_list_view(&self, area: Rect, buf: &mut Buffer) {
+        let (list_area, main_area, recent_area) = list_view_layout(area);
+        self.render_list(list_area, buf);
+        self.render_main(main_area, buf);
+        self.render_recent(recent_area, buf);
+    }
+
+    /// The index of the source shown at the given position of the app rendered to `area`, in
+    /// the sources list or the grid.
+    pub fn source_at(&self, area: Rect, position: Position) -> Option<usize> {
+        if self.grid {
+            let (tile_areas, _) = grid_layout(area, self.sources.len());
+            return tile_areas
+                .iter()
+                .take(self.sources.len())
+                .position(|tile_area| tile_area.contains(position));
+        }
+        let (list_area, _, _) = list_view_layout(area);
+        let items_area = list_area.inner(Margin::new(1, 1));
+        if !items_area.contains(position) {
+            return None;
+        }
+        // the list scrolls just far enough to show the selected source
+        let offset = self
+            .selected
+            .saturating_sub(items_area.height.saturating_sub(1) as usize);
+        let index = offset + (position.y - items_area.y) as usize;
+        (index < self.sources.len()).then_some(index)
+    }
+
+    /// Is the list of sources shown at the given position of the app rendered to `area`?
+    pub fn is_list_at(&self, area: Rect, position: Position) -> bool {
+        let (list_area, _, _) = list_view_layout(area);
+        !self.grid && list_area.contains(position)
+    }
+
+    /// Is the ferris of the selected source shown at the given position of the app rendered
+    /// to `area`?
+    pub fn is_ferris_at(&self, area: Rect, position: Position) -> bool {
+        if self.grid {
+            let (tile_areas, _) = grid_layout(area, self.sources.len());
+            return tile_areas
+                .get(self.selected)
+                .is_some_and(|tile_area| tile_area.contains(position));
+        }
+        let (_, main_area, _) = list_view_layout(area);
+        let (top_area, _, _) = main_layout(main_area);
+        let frame = self
+            .art
+            .frame(self.ferris_mood(self.selected), self.animation);
+        let (width, height) = frame_size(frame);
+        let ferris_area = Rect {
+            x: top_area.x + top_area.width.saturating_sub(width as u16) / 2,
+            y: top_area.y + INFO_LINES,
+            width: width as u16,
+            height: height as u16,
+        }
+        .intersection(top_area);
+        ferris_area.contains(position)
     }
 }
 
 impl Widget for &App {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .margin(2)
-            .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)])
-            .split(area);
+        if self.grid {
+            self.render_grid(area, buf);
+        } else {
+            self.render_list_view(area, buf);
+        }
+        if self.help {
+            self.render_help(area, buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_lines_are_padded_to_the_same_width() {
+        let lines = frame_lines("\n  /\\  \n /__\\\n", Style::new());
+        let lines: Vec<String> = lines.iter().map(Line::to_string).collect();
+        assert_eq!(lines, vec!["     ", "  /\\ ", " /__\\"]);
+    }
 
-        self.render_list(chunks[0], buf);
-        self.render_main(chunks[1], buf);
+    #[test]
+    fn relative_time_picks_largest_unit() {
+        assert_eq!(relative_time(TimeDelta::seconds(12)), "just now");
+        assert_eq!(relative_time(TimeDelta::minutes(42)), "42m ago");
+        assert_eq!(relative_time(TimeDelta::hours(5)), "5h ago");
+        assert_eq!(relative_time(TimeDelta::days(3)), "3d ago");
+        assert_eq!(relative_time(TimeDelta::days(30)), "4w ago");
+    }
+
+    #[test]
+    fn hungry_text_tells_ongoing_from_past_spells() {
+        let now = Utc::now();
+        let ongoing = HungrySpell {
+            start: now - TimeDelta::days(3),
+            end: None,
+        };
+        assert_eq!(hungry_text(Some(ongoing), now), " (hungry for 3d)");
+        let past = HungrySpell {
+            start: now - TimeDelta::days(30),
+            end: Some(now - TimeDelta::days(16)),
+        };
+        assert_eq!(
+            hungry_text(Some(past), now),
+            " (last hungry for 2w, 2w ago)"
+        );
+        assert_eq!(hungry_text(None, now), "");
+    }
+
+    #[test]
+    fn activity_line_shows_what_where_and_who() {
+        let now = Utc::now();
+        let commit = Activity {
+            timestamp: now - TimeDelta::hours(3),
+            kind: ActivityKind::Commit,
+            actor: Some("dawe".into()),
+            email: None,
+            git_ref: Some("refs/heads/main".into()),
+            message: Some("Feed the ferris".into()),
+        };
+        assert_eq!(
+            activity_line(&commit, now, &Theme::default()).to_string(),
+            "   3h ago [main] dawe: Feed the ferris"
+        );
+        let deletion = Activity {
+            kind: ActivityKind::Delete,
+            actor: None,
+            email: None,
+            message: None,
+            ..commit
+        };
+        assert_eq!(
+            activity_line(&deletion, now, &Theme::default()).to_string(),
+            "   3h ago [main] deleted"
+        );
+    }
+
+    #[test]
+    fn grid_keeps_tiles_as_big_as_possible() {
+        assert_eq!(grid_dimensions(1, 100, 30), (1, 1));
+        assert_eq!(grid_dimensions(4, 96, 18), (2, 2));
+        assert_eq!(grid_dimensions(4, 200, 20), (4, 1));
+        assert_eq!(grid_dimensions(3, 40, 60), (1, 3));
+        assert_eq!(grid_dimensions(0, 100, 30), (1, 0));
+    }
+
+    #[test]
+    fn rate_limit_text_shows_budget_or_pause() {
+        let rate_limit = RateLimit {
+            limit: Some(60),
+            remaining: Some(42),
+            ..RateLimit::default()
+        };
+        assert_eq!(rate_limit_text(&rate_limit), "API budget: 42/60");
+
+        let rate_limit = RateLimit {
+            limit: Some(60),
+            remaining: Some(0),
+            paused_until: Some(Utc::now() + TimeDelta::minutes(30) + TimeDelta::seconds(5)),
+            ..RateLimit::default()
+        };
+        assert_eq!(
+            rate_limit_text(&rate_limit),
+            "API rate limited, pausing for 30m"
+        );
     }
 }